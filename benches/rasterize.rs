@@ -0,0 +1,66 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tiny_renderer::shading::FlatShader;
+use tiny_renderer::{Camera, ImageMeta, Mesh, Point3D, Projection, RGBPixel, Rasterizer};
+
+/*
+ * Time `draw_mesh` on african_head.obj with each rasterizer. Run with
+ * `cargo bench`; the flat shader keeps the fragment stage cheap so the
+ * numbers are dominated by coverage testing.
+ */
+
+const SIZE: usize = 1024;
+const FRAMES: u32 = 10;
+
+fn frame_time(meshes: &[Mesh], rasterizer: Rasterizer, shader: &mut FlatShader) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..FRAMES {
+        let mut image = ImageMeta::new(SIZE, SIZE).unwrap();
+        image.rasterizer = rasterizer;
+        for mesh in meshes.iter() {
+            image.draw_mesh(mesh, shader);
+        }
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    let (meshes, _) = Mesh::load_obj(Path::new("./data/african_head.obj")).unwrap();
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: 45.0_f32.to_radians(),
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let mut shader = FlatShader {
+        transform: camera.view_projection_matrix(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        },
+    };
+
+    let bbox = frame_time(&meshes, Rasterizer::BoundingBox, &mut shader);
+    let edges = frame_time(&meshes, Rasterizer::EdgeFunction, &mut shader);
+
+    println!(
+        "{}x{} african_head.obj, mean of {} frames",
+        SIZE, SIZE, FRAMES
+    );
+    println!("bounding box:   {:>8.2} ms", bbox.as_secs_f64() * 1e3);
+    println!("edge functions: {:>8.2} ms", edges.as_secs_f64() * 1e3);
+    println!(
+        "speedup:        {:>8.2}x",
+        bbox.as_secs_f64() / edges.as_secs_f64()
+    );
+}
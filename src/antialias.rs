@@ -0,0 +1,190 @@
+use error::Error;
+use {ImageMeta, RGBPixel};
+
+/*
+ * Anti-aliasing in two flavours. Multisampling (`ImageMeta::multisample`)
+ * keeps several color and depth samples per pixel while drawing and is
+ * resolved here by averaging them. Supersampling renders a whole image
+ * `factor` times larger in each direction and filters it down with
+ * `ImageMeta::downsample`; it costs a full shader run per sample but also
+ * smooths texture and shading detail, not just triangle edges.
+ */
+
+/**
+ * Reconstruction filter for `ImageMeta::downsample`.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Filter {
+    // the plain average of the `factor` x `factor` block under each pixel.
+    Box,
+    // weights falling off linearly to zero one output pixel away from the
+    // pixel's center; blurs slightly but leaves less stair-stepping.
+    Tent,
+}
+
+impl Filter {
+    /**
+     * Taps along one axis as (offset, weight) pairs, with offsets in source
+     * pixels from the start of the block under an output pixel.
+     */
+    fn taps(self, factor: usize) -> Vec<(isize, f32)> {
+        let f = factor as isize;
+
+        match self {
+            Filter::Box => (0..f).map(|i| (i, 1.0)).collect(),
+            Filter::Tent => {
+                let center = factor as f32 / 2.0;
+                (-f..2 * f)
+                    .map(|i| {
+                        let distance = (i as f32 + 0.5 - center).abs();
+                        (i, 1.0 - distance / factor as f32)
+                    })
+                    .filter(|&(_, weight)| weight > 0.0)
+                    .collect()
+            }
+        }
+    }
+}
+
+impl ImageMeta {
+    /**
+     * Bring the samples up to date with `buffer` and `zbuffer` before a
+     * mesh is drawn. At first, or after the sample count changes, every
+     * pixel is copied into its samples. After that only pixels that no
+     * longer match their resolved samples are copied, because something
+     * drew straight into the image since the last `resolve`.
+     */
+    pub(crate) fn sync_samples(&mut self) {
+        let n = self.multisample.samples();
+
+        if self.samples.len() != self.buffer.len() * n {
+            self.samples = self
+                .buffer
+                .iter()
+                .flat_map(|&pixel| std::iter::repeat_n(pixel, n))
+                .collect();
+            self.sample_depths = self
+                .zbuffer
+                .iter()
+                .flat_map(|&depth| std::iter::repeat_n(depth, n))
+                .collect();
+            return;
+        }
+
+        for i in 0..self.buffer.len() {
+            let (pixel, depth) = (self.buffer[i], self.zbuffer[i]);
+            if resolved(&self.samples, &self.sample_depths, i, n) != (pixel, depth) {
+                for sample in self.samples[i * n..(i + 1) * n].iter_mut() {
+                    *sample = pixel;
+                }
+                for sample in self.sample_depths[i * n..(i + 1) * n].iter_mut() {
+                    *sample = depth;
+                }
+            }
+        }
+    }
+
+    /**
+     * Average the samples of every pixel into `buffer`; `zbuffer` keeps the
+     * nearest of their depths.
+     */
+    pub(crate) fn resolve(&mut self) {
+        let n = self.multisample.samples();
+
+        for i in 0..self.buffer.len() {
+            let (pixel, depth) = resolved(&self.samples, &self.sample_depths, i, n);
+            self.buffer[i] = pixel;
+            self.zbuffer[i] = depth;
+        }
+    }
+
+    /**
+     * A copy of the image `factor` times smaller in each direction, for
+     * supersampling: render at `factor` times the wanted size, then filter
+     * down. Rows and columns past the last whole block are dropped. The
+     * z-buffer keeps the nearest depth of each block.
+     */
+    pub fn downsample(&self, factor: usize, filter: Filter) -> Result<ImageMeta, Error> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut image = ImageMeta::new(
+            width.checked_div(factor).unwrap_or(0),
+            height.checked_div(factor).unwrap_or(0),
+        )?;
+        image.interpolation = self.interpolation;
+        image.rasterizer = self.rasterizer;
+        image.threads = self.threads;
+        image.multisample = self.multisample;
+
+        let taps = filter.taps(factor);
+
+        for row in 0..image.height as usize {
+            for col in 0..image.width as usize {
+                let mut sum = [0.0; 3];
+                let mut total = 0.0;
+
+                // taps past the border are left out and the rest weighted
+                // up to make up for them.
+                for &(dy, wy) in taps.iter() {
+                    let y = (row * factor) as isize + dy;
+                    if y < 0 || y >= height as isize {
+                        continue;
+                    }
+                    for &(dx, wx) in taps.iter() {
+                        let x = (col * factor) as isize + dx;
+                        if x < 0 || x >= width as isize {
+                            continue;
+                        }
+
+                        let p = self.buffer[y as usize * width + x as usize];
+                        let weight = wx * wy;
+                        sum[0] += p.red as f32 * weight;
+                        sum[1] += p.green as f32 * weight;
+                        sum[2] += p.blue as f32 * weight;
+                        total += weight;
+                    }
+                }
+
+                let mut depth = f32::MIN;
+                for y in row * factor..(row + 1) * factor {
+                    for x in col * factor..(col + 1) * factor {
+                        depth = depth.max(self.zbuffer[y * width + x]);
+                    }
+                }
+
+                let idx = row * image.width as usize + col;
+                image.buffer[idx] = RGBPixel {
+                    red: (sum[0] / total).round() as u8,
+                    green: (sum[1] / total).round() as u8,
+                    blue: (sum[2] / total).round() as u8,
+                };
+                image.zbuffer[idx] = depth;
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+// the color and depth pixel `i` resolves to from its `n` samples.
+fn resolved(samples: &[RGBPixel], depths: &[f32], i: usize, n: usize) -> (RGBPixel, f32) {
+    let sum = samples[i * n..(i + 1) * n]
+        .iter()
+        .fold([0; 3], |sum: [usize; 3], p| {
+            [
+                sum[0] + p.red as usize,
+                sum[1] + p.green as usize,
+                sum[2] + p.blue as usize,
+            ]
+        });
+    let pixel = RGBPixel {
+        red: ((sum[0] + n / 2) / n) as u8,
+        green: ((sum[1] + n / 2) / n) as u8,
+        blue: ((sum[2] + n / 2) / n) as u8,
+    };
+    let depth = depths[i * n..(i + 1) * n]
+        .iter()
+        .cloned()
+        .fold(f32::MIN, f32::max);
+
+    (pixel, depth)
+}
@@ -0,0 +1,195 @@
+use geometry::Point3D;
+use matrix::Matrix4x4;
+
+/*
+ * Definition of a Camera and implementation.
+ *
+ * The camera follows the OpenGL conventions: in view space it sits at the
+ * origin looking down the negative z-axis, and its projection maps the
+ * viewing volume onto the [-1,1]^3 cube with the near plane at z = -1.
+ */
+#[derive(Debug, Copy, Clone)]
+pub enum Projection {
+    // vertical field of view in radians; aspect is width / height.
+    Perspective {
+        fovy: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    },
+    Orthographic {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Camera {
+    pub eye: Point3D,
+    pub target: Point3D,
+    pub up: Point3D,
+    pub projection: Projection,
+}
+
+impl Camera {
+    pub fn look_at(eye: Point3D, target: Point3D, up: Point3D, projection: Projection) -> Camera {
+        Camera {
+            eye,
+            target,
+            up,
+            projection,
+        }
+    }
+
+    /**
+     * Whether the eye sits on the target or looks along `up`, leaving the
+     * camera's axes, and so `view_matrix`, undefined.
+     */
+    pub fn is_degenerate(&self) -> bool {
+        let forward = self.target - self.eye;
+        let side = forward * self.up;
+
+        side.length() <= 1e-6 * forward.length() * self.up.length()
+    }
+
+    /**
+     * World to view space. The rows are the camera's right, up and backward
+     * axes, followed by a translation of the eye to the origin.
+     *
+     *   [ s.x  s.y  s.z  -s.eye ]
+     *   [ u.x  u.y  u.z  -u.eye ]
+     *   [-f.x -f.y -f.z   f.eye ]
+     *   [  0    0    0      1   ]
+     */
+    pub fn view_matrix(&self) -> Matrix4x4 {
+        let mut f = self.target - self.eye;
+        f.normalize();
+
+        let mut s = f * self.up;
+        s.normalize();
+
+        let u = s * f;
+
+        Matrix4x4 {
+            buffer: [
+                s.x,
+                s.y,
+                s.z,
+                -s.dot(self.eye),
+                u.x,
+                u.y,
+                u.z,
+                -u.dot(self.eye),
+                -f.x,
+                -f.y,
+                -f.z,
+                f.dot(self.eye),
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+            ],
+        }
+    }
+
+    /**
+     * View to clip space.
+     *
+     *   Perspective, with c = 1 / tan(fovy / 2):
+     *
+     *   [ c/aspect  0        0                  0           ]
+     *   [    0      c        0                  0           ]
+     *   [    0      0  (f+n)/(n-f)        2*f*n/(n-f)       ]
+     *   [    0      0       -1                  0           ]
+     *
+     *   Orthographic:
+     *
+     *   [ 2/(r-l)    0        0      -(r+l)/(r-l) ]
+     *   [    0    2/(t-b)     0      -(t+b)/(t-b) ]
+     *   [    0       0     -2/(f-n)  -(f+n)/(f-n) ]
+     *   [    0       0        0            1      ]
+     */
+    pub fn projection_matrix(&self) -> Matrix4x4 {
+        let mut mat = Matrix4x4::zero();
+
+        match self.projection {
+            Projection::Perspective {
+                fovy,
+                aspect,
+                near,
+                far,
+            } => {
+                let c = 1.0 / (fovy / 2.0).tan();
+
+                mat.set(0, 0, c / aspect);
+                mat.set(1, 1, c);
+                mat.set(2, 2, (far + near) / (near - far));
+                mat.set(2, 3, 2.0 * far * near / (near - far));
+                mat.set(3, 2, -1.0);
+            }
+            Projection::Orthographic {
+                left,
+                right,
+                bottom,
+                top,
+                near,
+                far,
+            } => {
+                mat.set(0, 0, 2.0 / (right - left));
+                mat.set(1, 1, 2.0 / (top - bottom));
+                mat.set(2, 2, -2.0 / (far - near));
+                mat.set(0, 3, -(right + left) / (right - left));
+                mat.set(1, 3, -(top + bottom) / (top - bottom));
+                mat.set(2, 3, -(far + near) / (far - near));
+                mat.set(3, 3, 1.0);
+            }
+        }
+
+        mat
+    }
+
+    /**
+     * World to clip space: projection * view.
+     */
+    pub fn view_projection_matrix(&self) -> Matrix4x4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    /*  The viewport matrix maps the [-1,1]^3 cube to the image space we
+     *  render in. Depth is flipped so the near plane lands on 1 and the
+     *  far plane on 0; larger z is closer, as the z-buffer expects.
+     *
+     *  T:  [-1,1]^3 --> [0,w] x [0,h] x [0,1]
+     *
+     *   [ w/2  0     0    w/2 ]
+     *   [  0  h/2    0    h/2 ]
+     *   [  0   0   -1/2   1/2 ]
+     *   [  0   0     0     1  ]
+     */
+    pub fn viewport_matrix(width: f32, height: f32) -> Matrix4x4 {
+        Matrix4x4 {
+            buffer: [
+                width / 2.0,
+                0.0,
+                0.0,
+                width / 2.0,
+                0.0,
+                height / 2.0,
+                0.0,
+                height / 2.0,
+                0.0,
+                0.0,
+                -0.5,
+                0.5,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+            ],
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use geometry::Point3D;
+use shader::Varying;
+
+/*
+ * Sutherland-Hodgman clipping in homogeneous clip space.
+ *
+ * A clip space point (x, y, z, w) is inside the view frustum when
+ *
+ *   -w <= x <= w,  -w <= y <= w,  -w <= z <= w
+ *
+ * Clipping happens before the perspective divide, so geometry behind the
+ * camera (w <= 0) is cut away instead of being mirrored through the eye.
+ */
+
+pub type ClipVertex<V> = ([f32; 4], V);
+
+// signed distance to each of the six planes; non-negative means inside.
+const PLANES: [fn(&[f32; 4]) -> f32; 6] = [
+    |p| p[3] + p[0], // left
+    |p| p[3] - p[0], // right
+    |p| p[3] + p[1], // bottom
+    |p| p[3] - p[1], // top
+    |p| p[3] + p[2], // near
+    |p| p[3] - p[2], // far
+];
+
+pub fn is_inside(position: &[f32; 4]) -> bool {
+    PLANES.iter().all(|plane| plane(position) >= 0.0)
+}
+
+/**
+ * Clip a triangle against the view frustum. The result is a convex polygon
+ * with its vertices in the winding order of the input; it is empty when the
+ * triangle lies completely outside.
+ */
+pub fn clip_triangle<V: Varying>(triangle: &[ClipVertex<V>; 3]) -> Vec<ClipVertex<V>> {
+    let mut polygon: Vec<ClipVertex<V>> = triangle.to_vec();
+
+    if triangle.iter().all(|vert| is_inside(&vert.0)) {
+        return polygon;
+    }
+
+    for plane in PLANES.iter() {
+        if polygon.is_empty() {
+            break;
+        }
+        polygon = clip_polygon(&polygon, *plane);
+    }
+
+    polygon
+}
+
+/**
+ * Clip the segment from `a` to `b` against the view frustum (Liang-Barsky).
+ * Returns the part inside, or `None` when there is none.
+ */
+pub fn clip_line(a: [f32; 4], b: [f32; 4]) -> Option<([f32; 4], [f32; 4])> {
+    let (mut t0, mut t1) = (0.0, 1.0);
+
+    for plane in PLANES.iter() {
+        let (d_a, d_b) = (plane(&a), plane(&b));
+
+        if d_a < 0.0 && d_b < 0.0 {
+            return None;
+        }
+        // the segment crosses the plane at t; keep the inner side.
+        if d_a < 0.0 {
+            t0 = f32::max(t0, d_a / (d_a - d_b));
+        } else if d_b < 0.0 {
+            t1 = f32::min(t1, d_a / (d_a - d_b));
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+    Some((lerp_position(&a, &b, t0), lerp_position(&a, &b, t1)))
+}
+
+/**
+ * Clip the segment from `a` to `b` to the rectangle from `min` to `max`:
+ * the two dimensional version of `clip_line`, for lines in image space. It
+ * works in f64 so end points far outside still give accurate crossings.
+ */
+pub fn clip_to_rect(
+    a: (f64, f64),
+    b: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    // signed distance to each edge; non-negative means inside.
+    let distances = |p: (f64, f64)| [p.0 - min.0, max.0 - p.0, p.1 - min.1, max.1 - p.1];
+    let (d_a, d_b) = (distances(a), distances(b));
+    let (mut t0, mut t1) = (0.0, 1.0);
+
+    for (&d_a, &d_b) in d_a.iter().zip(d_b.iter()) {
+        if d_a < 0.0 && d_b < 0.0 {
+            return None;
+        }
+        if d_a < 0.0 {
+            t0 = f64::max(t0, d_a / (d_a - d_b));
+        } else if d_b < 0.0 {
+            t1 = f64::min(t1, d_a / (d_a - d_b));
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+    let lerp = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    Some((lerp(t0), lerp(t1)))
+}
+
+/**
+ * Split a convex polygon into a fan of triangles sharing its first vertex.
+ */
+pub fn triangulate<V: Varying>(polygon: &[ClipVertex<V>]) -> Vec<[ClipVertex<V>; 3]> {
+    (1..polygon.len().saturating_sub(1))
+        .map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+        .collect()
+}
+
+fn clip_polygon<V: Varying>(
+    polygon: &[ClipVertex<V>],
+    plane: fn(&[f32; 4]) -> f32,
+) -> Vec<ClipVertex<V>> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+
+    for idx in 0..polygon.len() {
+        let current = polygon[idx];
+        let next = polygon[(idx + 1) % polygon.len()];
+
+        let d_current = plane(&current.0);
+        let d_next = plane(&next.0);
+
+        if d_current >= 0.0 {
+            clipped.push(current);
+        }
+
+        // the edge crosses the plane; keep the intersection point.
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            clipped.push(lerp(&current, &next, t));
+        }
+    }
+
+    clipped
+}
+
+fn lerp_position(a: &[f32; 4], b: &[f32; 4], t: f32) -> [f32; 4] {
+    let mut position = [0.0; 4];
+    for (idx, coord) in position.iter_mut().enumerate() {
+        *coord = a[idx] + (b[idx] - a[idx]) * t;
+    }
+    position
+}
+
+fn lerp<V: Varying>(a: &ClipVertex<V>, b: &ClipVertex<V>, t: f32) -> ClipVertex<V> {
+    let position = lerp_position(&a.0, &b.0, t);
+
+    let weights = Point3D {
+        x: 1.0 - t,
+        y: t,
+        z: 0.0,
+    };
+
+    (position, V::interpolate(&[a.1, b.1, b.1], weights))
+}
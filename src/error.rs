@@ -0,0 +1,135 @@
+use imagefmt;
+use mesh::MeshError;
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use tobj;
+
+/*
+ * Error type shared by every fallible part of the crate: loading meshes and
+ * textures, reading scene files, building framebuffers and writing images.
+ */
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    // an image could not be decoded or encoded.
+    Image(imagefmt::Error),
+    // an OBJ file could not be read or parsed.
+    Obj(tobj::LoadError),
+    // the mesh data itself is inconsistent.
+    Mesh(MeshError),
+    InvalidDimensions {
+        width: usize,
+        height: usize,
+    },
+    // a texel buffer whose length does not match the size of its texture.
+    TexelCount {
+        expected: usize,
+        found: usize,
+    },
+    // no image format goes with this file extension, empty when the path
+    // has none.
+    UnsupportedFormat(String),
+    // a matrix with no inverse.
+    SingularMatrix,
+    // a scene node refers to a mesh the scene does not hold.
+    UnknownMesh {
+        node: String,
+        index: usize,
+    },
+    // a scene file is malformed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    // the file named on a line of a scene file could not be loaded.
+    Load {
+        line: usize,
+        path: PathBuf,
+        source: Box<Error>,
+    },
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Image(ref err) => write!(f, "could not process image: {}", err),
+            Error::Obj(ref err) => write!(f, "could not load OBJ file: {}", err),
+            Error::Mesh(ref err) => write!(f, "malformed mesh: {}", err),
+            Error::InvalidDimensions { width, height } => {
+                write!(f, "invalid framebuffer dimensions {}x{}", width, height)
+            }
+            Error::TexelCount { expected, found } => {
+                write!(f, "expected {} texel bytes, found {}", expected, found)
+            }
+            Error::UnsupportedFormat(ref extension) => {
+                write!(f, "no image format for the extension '{}'", extension)
+            }
+            Error::SingularMatrix => write!(f, "matrix is singular"),
+            Error::UnknownMesh { ref node, index } => {
+                write!(f, "node '{}' refers to missing mesh {}", node, index)
+            }
+            Error::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+            Error::Load {
+                line,
+                ref path,
+                ref source,
+            } => write!(
+                f,
+                "line {}: cannot load '{}': {}",
+                line,
+                path.display(),
+                source
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Image(ref err) => Some(err),
+            Error::Obj(ref err) => Some(err),
+            Error::Mesh(ref err) => Some(err),
+            Error::Load { ref source, .. } => Some(&**source),
+            Error::InvalidDimensions { .. }
+            | Error::TexelCount { .. }
+            | Error::UnsupportedFormat(_)
+            | Error::SingularMatrix
+            | Error::UnknownMesh { .. }
+            | Error::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<imagefmt::Error> for Error {
+    fn from(err: imagefmt::Error) -> Error {
+        match err {
+            imagefmt::Error::Io(err) => Error::Io(err),
+            err => Error::Image(err),
+        }
+    }
+}
+
+impl From<tobj::LoadError> for Error {
+    fn from(err: tobj::LoadError) -> Error {
+        Error::Obj(err)
+    }
+}
+
+impl From<MeshError> for Error {
+    fn from(err: MeshError) -> Error {
+        Error::Mesh(err)
+    }
+}
@@ -41,6 +41,23 @@ impl Sub for Point {
     }
 }
 
+impl Mul<f32> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: f32) -> Point {
+        Point {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Point) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
 /*
  * Definition of a 3D Point and implementation.
  */
@@ -79,9 +96,9 @@ impl Point3D {
 
     pub fn normalize(&mut self) {
         let _length = self.length();
-        self.x = self.x / _length;
-        self.y = self.y / _length;
-        self.z = self.z / _length;
+        self.x /= _length;
+        self.y /= _length;
+        self.z /= _length;
     }
 
     pub fn dot(self, other: Point3D) -> f32 {
@@ -127,7 +144,7 @@ impl Mul for Point3D {
         let z = self.x * other.y - other.x * self.y;
 
         // for current application doesn't need to be normalized.
-        Point3D { x: x, y: -y, z: z }
+        Point3D { x, y: -y, z }
     }
 }
 
@@ -169,13 +186,15 @@ impl Mul<f32> for Point3D {
     }
 }
 
+type DeterminantFn = dyn Fn(&[Point3D]) -> f32;
+
 #[allow(dead_code)]
 struct LazyDeterminant {
-    func: Box<Fn(&Vec<Point3D>) -> f32>,
+    func: Box<DeterminantFn>,
 }
 
 #[allow(dead_code)]
-#[derive(Copy, Clone)] // copy is byte to byte copy
+#[derive(Debug, Copy, Clone)] // copy is byte to byte copy
 pub struct Triangle {
     pub vertices: [Point3D; 3],
 }
@@ -213,7 +232,7 @@ impl Triangle {
     }
 
     pub fn barycentric_coords_f32(&self, x: f32, y: f32) -> Point3D {
-        let point = Point3D { x: x, y: y, z: 0.0 };
+        let point = Point3D { x, y, z: 0.0 };
         self.barycentric_coords(point)
     }
 
@@ -242,18 +261,18 @@ impl Triangle {
             left_col.x * right_col.y - left_col.y * right_col.x
         }
 
-        let point = Point3D { x: x, y: y, z: 0.0 };
+        let point = Point3D { x, y, z: 0.0 };
         let edges: Vec<Point3D> = self.vertices.iter().map(|vert| *vert - point).collect();
 
         let lazy_barycentric_coords = [
             LazyDeterminant {
-                func: Box::new(|cols: &Vec<Point3D>| determinant_2d(&cols[1], &cols[2])),
+                func: Box::new(|cols: &[Point3D]| determinant_2d(&cols[1], &cols[2])),
             },
             LazyDeterminant {
-                func: Box::new(|cols: &Vec<Point3D>| -determinant_2d(&cols[0], &cols[2])),
+                func: Box::new(|cols: &[Point3D]| -determinant_2d(&cols[0], &cols[2])),
             },
             LazyDeterminant {
-                func: Box::new(|cols: &Vec<Point3D>| determinant_2d(&cols[0], &cols[1])),
+                func: Box::new(|cols: &[Point3D]| determinant_2d(&cols[0], &cols[1])),
             },
         ];
 
@@ -267,15 +286,11 @@ impl Triangle {
     }
 
     pub fn point_in_triangle(&self, x: f32, y: f32) -> bool {
-        let point = Point3D { x: x, y: y, z: 0.0 };
+        let point = Point3D { x, y, z: 0.0 };
         let bc = self.barycentric_coords(point);
 
         // the entire OR chain will not evaluate if the first
         // or the second coordinate is < 0. See https://doc.rust-lang.org/reference/expressions/operator-expr.html#lazy-boolean-operators
-        if bc.x < 0.0 || bc.y < 0.0 || bc.z < 0.0 {
-            false
-        } else {
-            true
-        }
+        !(bc.x < 0.0 || bc.y < 0.0 || bc.z < 0.0)
     }
 }
@@ -1,41 +1,172 @@
+pub extern crate generic_array;
 pub extern crate imagefmt;
+pub extern crate tobj;
 pub extern crate typenum;
 
+pub mod antialias;
+pub mod camera;
+pub mod clip;
+pub mod error;
 pub mod geometry;
+pub mod line;
 pub mod matrix;
-
+pub mod mesh;
+pub mod netpbm;
+mod raster;
+pub mod scene;
+pub mod scene_file;
+pub mod shader;
+pub mod shading;
+pub mod shadow;
+pub mod texture;
+pub mod vector;
+pub mod wireframe;
+
+pub use antialias::Filter;
+pub use camera::{Camera, Projection};
+pub use error::Error;
 pub use geometry::{Point, Point3D, Triangle};
-use imagefmt::{ColFmt, ColType, Image};
+use imagefmt::{ColFmt, ColType};
+pub use line::{Cap, Join, Stroke};
+pub use mesh::{Face, Mesh, MeshError};
+pub use scene::{Node, Scene};
+pub use scene_file::SceneFile;
+pub use shader::{Derivatives, Shader, Varying, Vertex};
 use std::f32;
+use std::fs;
+use std::path::Path;
+use std::thread;
 use std::vec::Vec;
+pub use texture::{Rgba, Texture, TextureFilter, TextureFormat, Wrap};
+pub use vector::{Vec2, Vec3, Vec4, Vector};
+pub use wireframe::{Wireframe, WireframeMode};
 
-#[derive(Copy, Clone)] // copy is byte to byte copy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)] // copy is byte to byte copy
 pub struct RGBPixel {
     pub red: u8,
     pub green: u8,
     pub blue: u8,
 }
 
+impl RGBPixel {
+    // scale every channel by an intensity in [0, 1].
+    pub fn scale(self, intensity: f32) -> RGBPixel {
+        RGBPixel {
+            red: (self.red as f32 * intensity) as u8,
+            green: (self.green as f32 * intensity) as u8,
+            blue: (self.blue as f32 * intensity) as u8,
+        }
+    }
+}
+
+/**
+ * How `draw_mesh` interpolates varyings across a triangle.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interpolation {
+    // divide by w before and after interpolating, so varyings are linear in
+    // world space rather than on the screen.
+    Perspective,
+    // linear in screen space. Textures warp under a perspective camera;
+    // only useful for comparison images.
+    Affine,
+}
+
+/**
+ * How `draw_mesh` finds the pixels covered by a triangle.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rasterizer {
+    // edge functions in fixed point, set up once per triangle and stepped
+    // incrementally from pixel to pixel. Follows the top-left fill rule, so
+    // meshes are drawn without cracks or overdraw.
+    EdgeFunction,
+    // barycentric coordinates recomputed at every pixel of the bounding
+    // box, counting edges as inside. Slower and draws shared edges twice;
+    // kept as a reference for tests and benchmarks.
+    BoundingBox,
+}
+
+/**
+ * Samples per pixel for `draw_mesh`. With more than one, coverage and depth
+ * are tested at every sample but the fragment shader still runs once per
+ * pixel; the samples are averaged into `buffer` after each mesh. Pixels
+ * changed in `buffer` or `zbuffer` between meshes, e.g. by a line, have
+ * their samples reset to the new color and depth.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Multisample {
+    Off,
+    X2,
+    X4,
+    X8,
+}
+
+impl Multisample {
+    pub fn samples(self) -> usize {
+        match self {
+            Multisample::Off => 1,
+            Multisample::X2 => 2,
+            Multisample::X4 => 4,
+            Multisample::X8 => 8,
+        }
+    }
+}
+
 // T is the pixel type
 #[allow(dead_code)]
 pub struct ImageMeta {
     pub buffer: Vec<RGBPixel>,
+    pub zbuffer: Vec<f32>,
     pub width: i32,
     pub height: i32,
+    pub interpolation: Interpolation,
+    pub rasterizer: Rasterizer,
+    // worker threads for `draw_mesh`; more than one rasterizes bands of
+    // rows in parallel.
+    pub threads: usize,
+    pub multisample: Multisample,
+    // color and depth of every sample while multisampling, seeded from
+    // `buffer` and `zbuffer` when the sample count changes.
+    samples: Vec<RGBPixel>,
+    sample_depths: Vec<f32>,
 }
 
+/**
+ * The most pixels an `ImageMeta` may have, a 16384x16384 image. Its color
+ * and depth buffers take 7 bytes per pixel, close to 2 GB at this size.
+ */
+pub const MAX_PIXELS: usize = 1 << 28;
+
 #[allow(dead_code)]
 impl ImageMeta {
-    // shift float point coordinates in [0, N]^2 space
-    // to discrete coordinates
-    fn clamp(&self, num: f32) -> usize {
-        if num as i32 >= self.height {
-            (self.height - 1) as usize
-        } else if num <= 0.0 {
-            0
-        } else {
-            num.floor() as usize
+    /**
+     * A black image with an empty z-buffer. Both dimensions must be positive
+     * and the image must have at most `MAX_PIXELS`, so each fits an `i32`.
+     */
+    pub fn new(width: usize, height: usize) -> Result<ImageMeta, Error> {
+        if width == 0 || height == 0 || width.saturating_mul(height) > MAX_PIXELS {
+            return Err(Error::InvalidDimensions { width, height });
         }
+
+        let black = RGBPixel {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+
+        Ok(ImageMeta {
+            buffer: vec![black; width * height],
+            zbuffer: vec![f32::MIN; width * height],
+            width: width as i32,
+            height: height as i32,
+            interpolation: Interpolation::Perspective,
+            rasterizer: Rasterizer::EdgeFunction,
+            threads: 1,
+            multisample: Multisample::Off,
+            samples: Vec::new(),
+            sample_depths: Vec::new(),
+        })
     }
 
     /**
@@ -43,8 +174,8 @@ impl ImageMeta {
      * into the positive quadrant.
      */
     fn image_to_vector_index(&self, x: f32, y: f32) -> usize {
-        let row: usize = self.clamp(self.height as f32 - y);
-        let col: usize = self.clamp(x);
+        let row = raster::row_of(y, self.height as usize);
+        let col = raster::col_of(x, self.width as usize);
 
         ((self.width as usize) * row) + col
     }
@@ -58,18 +189,18 @@ impl ImageMeta {
         self.buffer[idx] = *color;
     }
 
+    /**
+     * A one pixel line between two points in image space, through
+     * `draw_line` on the pixels containing them.
+     */
     pub fn draw_lines_segment(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &RGBPixel) {
-        let steep = self.is_steep(x0, y0, x1, y1);
-
-        let mut func =
-            |x0, y0, x1, y1| self.draw_lines_segment_second(x0, y0, x1, y1, steep, color);
-
-        match (steep, x0 > x1, y0 > y1) {
-            (true, _, false) => func(y0, x0, y1, x1),
-            (true, _, true) => func(y1, x1, y0, x0),
-            (false, false, _) => func(x0, y0, x1, y1),
-            (false, true, _) => func(x1, y1, x0, y0),
-        }
+        self.draw_line(
+            x0.floor() as i32,
+            y0.floor() as i32,
+            x1.floor() as i32,
+            y1.floor() as i32,
+            color,
+        );
     }
 
     fn is_steep(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
@@ -101,29 +232,11 @@ impl ImageMeta {
         }
     }
 
-    fn draw_lines_segment_naive(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) {
-        let delta = 0.001; //step interval
-        let mut t: f32 = 0.0;
-        let pixel = RGBPixel {
-            red: 255,
-            green: 0,
-            blue: 0,
-        };
-
-        while t < 1.0 {
-            // from the parametric definition of line (x,y) = t(x1,y1) + (1-t)(x0,y0)
-            let x_t = x0 + (x1 - x0) * t;
-            let y_t = y0 + (y1 - y0) * t;
-            self.set_pixel(x_t, y_t, &pixel);
-
-            t += delta;
-        }
-    }
-
     /**
      *  Draw lines between two segments that share one and only one
      *  endpoint.
      */
+    #[allow(clippy::too_many_arguments)]
     fn draw_lines_between_segments(
         &mut self,
         x0: f32,
@@ -201,105 +314,278 @@ impl ImageMeta {
         }
     }
 
-    pub fn draw_triangle(&mut self, triangle: Triangle, zbuffer: &mut Vec<f32>, color: &RGBPixel) {
-        // draw outline of triangle
-        let (sw, ne) = triangle.compute_bbox();
-
-        let mut x = sw.x;
-
-        while x <= ne.x {
-            let mut y = sw.y;
-
-            while y <= ne.y {
-                let bc = triangle.barycentric_coords_f32(x, y);
-
-                if (bc.x >= 0.0) && (bc.y >= 0.0) && (bc.z >= 0.0) {
-                    let z = bc.x * triangle.vertices[0].z
-                        + bc.y * triangle.vertices[1].z
-                        + bc.z * triangle.vertices[2].z;
-                    let idx = self.image_to_vector_index(x, y);
-
-                    if zbuffer[idx] < z {
-                        zbuffer[idx] = z;
-                        self.set_pixel(x, y, color);
-                    }
-                }
-                y += 1.0;
-            }
-            x += 1.0;
-        }
+    /**
+     * Fill a screen space triangle with `color`, depth testing against
+     * `zbuffer`. Pixels are covered as by `draw_mesh`, so triangles sharing
+     * an edge draw its pixels once.
+     */
+    pub fn draw_triangle(&mut self, triangle: Triangle, zbuffer: &mut [f32], color: &RGBPixel) {
+        let shader = FillShader { color: *color };
+        self.fill_triangle(triangle, [(); 3], zbuffer, &shader);
     }
 
+    /**
+     * Fill `triangle` with `texture`, looked up at the interpolated texture
+     * coordinates in UV space and scaled by `intensity`.
+     */
     pub fn draw_triangle_with_texture(
         &mut self,
         triangle: Triangle,
         texture_coords: [Point3D; 3],
-        texture_img: &Image<u8>,
+        texture: &Texture,
         intensity: f32,
-        zbuffer: &mut Vec<f32>,
+        zbuffer: &mut [f32],
     ) {
-        let tga_clamp = |num: f32, dim: usize| -> usize {
-            if num as usize >= dim {
-                (num as usize) % dim
-            } else if num <= 0.0 {
-                0
-            } else {
-                num.floor() as usize
-            }
+        let uv = |p: Point3D| Vec2::new(p.x, p.y);
+        let varyings = [
+            uv(texture_coords[0]),
+            uv(texture_coords[1]),
+            uv(texture_coords[2]),
+        ];
+        let shader = TexelShader { texture, intensity };
+        self.fill_triangle(triangle, varyings, zbuffer, &shader);
+    }
+
+    // rasterize a screen space triangle as `draw_mesh` does, with the
+    // varyings interpolated linearly on screen.
+    fn fill_triangle<S: Shader>(
+        &mut self,
+        triangle: Triangle,
+        varyings: [S::Varying; 3],
+        zbuffer: &mut [f32],
+        shader: &S,
+    ) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut band = raster::Band {
+            buffer: &mut self.buffer,
+            zbuffer,
+            width,
+            height,
+            first_row: 0,
+            rows: height,
+            samples: 1,
+        };
+        let primitive = raster::Primitive {
+            triangle,
+            inv_w: Point3D::new([1.0, 1.0, 1.0]),
+            varyings,
         };
+        raster::rasterize(
+            &mut band,
+            Interpolation::Affine,
+            Rasterizer::EdgeFunction,
+            &primitive,
+            shader,
+        );
+    }
 
-        let index_tga_image = |u: f32, v: f32, w: usize, h: usize| -> usize {
-            // u is x, v is y
-            let row: usize = tga_clamp(h as f32 - v, h);
-            let col: usize = tga_clamp(u, w);
+    /**
+     * Map clip coordinates to image space through `Camera::viewport_matrix`.
+     */
+    fn viewport(&self, clip: [f32; 4]) -> Point3D {
+        let v = Camera::viewport_matrix(self.width as f32, self.height as f32) * clip;
 
-            w * row + col
-        };
+        Point3D {
+            x: v[0] / v[3],
+            y: v[1] / v[3],
+            z: v[2] / v[3],
+        }
+    }
 
-        // draw outline of triangle
-        let (sw, ne) = triangle.compute_bbox();
+    /**
+     * Render every face of `mesh` through `shader`, depth testing against
+     * the image's own z-buffer. The vertex stage always runs on the calling
+     * thread; with `threads` above one the triangles are then binned into
+     * bands of rows which are rasterized in parallel. The image is the same
+     * either way.
+     */
+    pub fn draw_mesh<S: Shader>(&mut self, mesh: &Mesh, shader: &mut S) {
+        if self.multisample != Multisample::Off {
+            self.sync_samples();
+        }
 
-        let mut x = sw.x;
+        if self.threads > 1 {
+            let mut primitives = Vec::with_capacity(mesh.num_faces());
+            for face in mesh.faces() {
+                self.assemble(&face, shader, &mut primitives);
+            }
+            self.rasterize_tiled(&primitives, shader);
+        } else {
+            let (interpolation, rasterizer) = (self.interpolation, self.rasterizer);
+            let mut primitives = Vec::new();
+            for face in mesh.faces() {
+                primitives.clear();
+                self.assemble(&face, shader, &mut primitives);
+
+                let mut band = self.whole_image();
+                for primitive in primitives.iter() {
+                    raster::rasterize(&mut band, interpolation, rasterizer, primitive, shader);
+                }
+            }
+        }
 
-        while x <= ne.x {
-            let mut y = sw.y;
+        if self.multisample != Multisample::Off {
+            self.resolve();
+        }
+    }
 
-            while y <= ne.y {
-                let bc = triangle.barycentric_coords_f32(x, y);
-                let up = (bc.x >= 0.0) && (bc.y >= 0.0) && (bc.z >= 0.0);
-                let dn = (bc.x <= 0.0) && (bc.y <= 0.0) && (bc.z <= 0.0);
-
-                if up || dn {
-                    let z = bc.x * triangle.vertices[0].z
-                        + bc.y * triangle.vertices[1].z
-                        + bc.z * triangle.vertices[2].z;
-                    let idx = self.image_to_vector_index(x, y);
-                    let interpolated_texture = bc.x * texture_coords[0]
-                        + texture_coords[1] * bc.y
-                        + texture_coords[2] * bc.z;
-
-                    if zbuffer[idx] < z {
-                        zbuffer[idx] = z;
-
-                        let texture_idx = 3 * index_tga_image(
-                            interpolated_texture.x,
-                            interpolated_texture.y,
-                            texture_img.w,
-                            texture_img.h,
-                        );
-
-                        let color = RGBPixel {
-                            red: ((texture_img.buf[texture_idx + 0] as f32) * intensity) as u8,
-                            green: ((texture_img.buf[texture_idx + 1] as f32) * intensity) as u8,
-                            blue: ((texture_img.buf[texture_idx + 2] as f32) * intensity) as u8,
-                        };
-                        self.set_pixel(x, y, &color);
-                    }
+    /**
+     * The color and depth buffers `draw_mesh` renders into, and the number
+     * of samples they hold per pixel.
+     */
+    fn render_targets(&mut self) -> (&mut [RGBPixel], &mut [f32], usize) {
+        let samples = self.multisample.samples();
+        if samples == 1 {
+            return (&mut self.buffer, &mut self.zbuffer, 1);
+        }
+        (&mut self.samples, &mut self.sample_depths, samples)
+    }
+
+    fn whole_image(&mut self) -> raster::Band<'_> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (buffer, zbuffer, samples) = self.render_targets();
+
+        raster::Band {
+            buffer,
+            zbuffer,
+            width,
+            height,
+            first_row: 0,
+            rows: height,
+            samples,
+        }
+    }
+
+    /**
+     * Run the vertex stage on a face and append the screen space triangles
+     * left after clipping to `primitives`.
+     */
+    fn assemble<S: Shader>(
+        &self,
+        face: &Face,
+        shader: &mut S,
+        primitives: &mut Vec<raster::Primitive<S::Varying>>,
+    ) {
+        let face_normal = Vec3::from(face.normal());
+        let mut vertices = Vec::with_capacity(3);
+
+        for j in 0..3 {
+            let normal = face
+                .normals
+                .map_or(face_normal, |normals| normals[j].into());
+            let texcoord = face
+                .texcoords
+                .map_or(Vec2::zero(), |texcoords| texcoords[j].into());
+            let tangent = face
+                .tangents
+                .map_or(Vec3::zero(), |tangents| tangents[j].into());
+            let bitangent = face
+                .bitangents
+                .map_or(Vec3::zero(), |bitangents| bitangents[j].into());
+
+            vertices.push(shader.vertex(&Vertex {
+                face: face.index,
+                nthvert: j,
+                position: face.triangle.vertices[j].into(),
+                normal,
+                texcoord,
+                tangent,
+                bitangent,
+                face_normal,
+            }));
+        }
+
+        // clip before the perspective divide; a clipped triangle comes
+        // back as a convex polygon which is drawn as a triangle fan.
+        let polygon = clip::clip_triangle(&[vertices[0], vertices[1], vertices[2]]);
+
+        for clipped in clip::triangulate(&polygon).iter() {
+            let triangle = Triangle {
+                vertices: [
+                    self.viewport(clipped[0].0),
+                    self.viewport(clipped[1].0),
+                    self.viewport(clipped[2].0),
+                ],
+            };
+
+            // 1/w is affine in screen space, so it can be interpolated
+            // with the screen space barycentric coordinates.
+            let inv_w = Point3D {
+                x: 1.0 / clipped[0].0[3],
+                y: 1.0 / clipped[1].0[3],
+                z: 1.0 / clipped[2].0[3],
+            };
+
+            primitives.push(raster::Primitive {
+                triangle,
+                inv_w,
+                varyings: [clipped[0].1, clipped[1].1, clipped[2].1],
+            });
+        }
+    }
+
+    /**
+     * Bin `primitives` into bands of `raster::BAND_ROWS` rows and hand the
+     * bands out round-robin to `threads` workers. Each band owns its slice
+     * of the color and depth buffers and draws its triangles in submission
+     * order.
+     */
+    fn rasterize_tiled<S: Shader>(
+        &mut self,
+        primitives: &[raster::Primitive<S::Varying>],
+        shader: &S,
+    ) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let band_count = height.div_ceil(raster::BAND_ROWS);
+
+        let mut bins: Vec<Vec<usize>> = vec![Vec::new(); band_count];
+        for (idx, primitive) in primitives.iter().enumerate() {
+            if let Some((top, bottom)) = raster::row_range(&primitive.triangle, width, height) {
+                for bin in bins[top / raster::BAND_ROWS..=bottom / raster::BAND_ROWS].iter_mut() {
+                    bin.push(idx);
                 }
-                y += 1.0;
             }
-            x += 1.0;
         }
+
+        let threads = self.threads.min(band_count);
+        let mut work: Vec<Vec<(raster::Band, &[usize])>> =
+            (0..threads).map(|_| Vec::new()).collect();
+
+        let (interpolation, rasterizer) = (self.interpolation, self.rasterizer);
+        let (buffer, zbuffer, samples) = self.render_targets();
+        let pixels = buffer.chunks_mut(width * samples * raster::BAND_ROWS);
+        let depths = zbuffer.chunks_mut(width * samples * raster::BAND_ROWS);
+
+        for (band_idx, (buffer, zbuffer)) in pixels.zip(depths).enumerate() {
+            let band = raster::Band {
+                rows: buffer.len() / (width * samples),
+                buffer,
+                zbuffer,
+                width,
+                height,
+                first_row: band_idx * raster::BAND_ROWS,
+                samples,
+            };
+            work[band_idx % threads].push((band, &bins[band_idx]));
+        }
+
+        thread::scope(|scope| {
+            for bands in work {
+                scope.spawn(move || {
+                    for (mut band, bin) in bands {
+                        for &idx in bin.iter() {
+                            raster::rasterize(
+                                &mut band,
+                                interpolation,
+                                rasterizer,
+                                &primitives[idx],
+                                shader,
+                            );
+                        }
+                    }
+                });
+            }
+        });
     }
 
     pub fn draw_triangles_line_sweep(&mut self, mut triangle: Triangle, color: &RGBPixel) {
@@ -331,28 +617,148 @@ impl ImageMeta {
         );
     }
 
-    pub fn to_tga_image(&self, filename: &str) -> () {
-        let vec_length = (self.width * self.height * 3) as usize;
-        let mut tga_buffer: Vec<u8> = Vec::with_capacity(vec_length);
+    // the color buffer as packed RGB bytes, top row first.
+    fn rgb_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.buffer.len() * 3);
 
         for pixel in self.buffer.iter() {
-            tga_buffer.push(pixel.red);
-            tga_buffer.push(pixel.green);
-            tga_buffer.push(pixel.blue);
+            bytes.push(pixel.red);
+            bytes.push(pixel.green);
+            bytes.push(pixel.blue);
+        }
+        bytes
+    }
+
+    // luma of every pixel with the Rec. 601 weights.
+    fn gray_bytes(&self) -> Vec<u8> {
+        self.buffer
+            .iter()
+            .map(|p| {
+                ((299 * p.red as u32 + 587 * p.green as u32 + 114 * p.blue as u32) / 1000) as u8
+            })
+            .collect()
+    }
+
+    /**
+     * Write the image to `path`, in the format given by its extension.
+     */
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        match Format::from_path(path) {
+            Some(format) => self.save_as(path, format),
+            None => Err(Error::UnsupportedFormat(
+                path.extension()
+                    .map(|ext| ext.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            )),
+        }
+    }
+
+    /**
+     * Write the image to `path` in `format`. The file is encoded in memory
+     * first, so a failed encoding leaves whatever was at `path` untouched.
+     */
+    pub fn save_as(&self, path: &Path, format: Format) -> Result<(), Error> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut writer = Vec::new();
+
+        match format {
+            Format::Png => imagefmt::png::write(
+                &mut writer,
+                w,
+                h,
+                ColFmt::RGB,
+                &self.rgb_bytes(),
+                ColType::Auto,
+                None,
+            )?,
+            Format::Tga => imagefmt::tga::write(
+                &mut writer,
+                w,
+                h,
+                ColFmt::RGB,
+                &self.rgb_bytes(),
+                ColType::Auto,
+                None,
+            )?,
+            Format::Ppm => netpbm::write_ppm(&mut writer, w, h, &self.rgb_bytes())?,
+            Format::Pgm => netpbm::write_pgm(&mut writer, w, h, &self.gray_bytes())?,
+        }
+
+        Ok(fs::write(path, writer)?)
+    }
+}
+
+/**
+ * Image file formats `ImageMeta` can be written in. PNG and TGA go through
+ * imagefmt, PPM and PGM are written as binary Netpbm files; PGM stores the
+ * luma of each pixel.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Tga,
+    Ppm,
+    Pgm,
+}
+
+impl Format {
+    // case-insensitive lookup of a file extension, without the dot.
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_lowercase().as_str() {
+            "png" => Some(Format::Png),
+            "tga" => Some(Format::Tga),
+            "ppm" => Some(Format::Ppm),
+            "pgm" => Some(Format::Pgm),
+            _ => None,
         }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Format> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Format::from_extension)
+    }
+}
+
+/**
+ * Read an image file as a texture, keeping gray levels and alpha.
+ */
+pub fn load_texture(path: &Path) -> Result<Texture, Error> {
+    Texture::from_image(imagefmt::read(path, ColFmt::Auto)?)
+}
+
+// fragment stages of the screen space triangle fills, which have no vertex
+// stage.
+struct FillShader {
+    color: RGBPixel,
+}
+
+impl Shader for FillShader {
+    type Varying = ();
+
+    fn vertex(&mut self, _: &Vertex) -> ([f32; 4], ()) {
+        unreachable!("screen space triangles skip the vertex stage")
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        Some(self.color)
+    }
+}
+
+struct TexelShader<'a> {
+    texture: &'a Texture,
+    intensity: f32,
+}
+
+impl<'a> Shader for TexelShader<'a> {
+    type Varying = Vec2;
+
+    fn vertex(&mut self, _: &Vertex) -> ([f32; 4], Vec2) {
+        unreachable!("screen space triangles skip the vertex stage")
+    }
 
-        assert_eq!(tga_buffer.len(), vec_length);
-
-        // write TGA image
-        imagefmt::write(
-            filename,
-            self.width as usize,
-            self.height as usize,
-            ColFmt::RGB,
-            &tga_buffer,
-            ColType::Auto,
-        )
-        .unwrap();
-        println!("image written to {}!", filename);
+    fn fragment(&self, uv: Vec2) -> Option<RGBPixel> {
+        let texel = self.texture.sample(uv.into());
+        Some(texel.to_pixel().scale(self.intensity))
     }
 }
@@ -0,0 +1,527 @@
+use clip;
+use geometry::Point;
+use {ImageMeta, RGBPixel};
+
+/*
+ * Line drawing in image space, with the origin in the lower left-hand
+ * corner. Pixel (x, y) covers the unit square with its lower left corner at
+ * (x, y), so its center is at (x + 0.5, y + 0.5); pixels off the image are
+ * skipped rather than clamped to the border.
+ *
+ * `draw_line` is an integer Bresenham walk between pixels, `draw_line_aa`
+ * a Xiaolin Wu line blending its coverage into the image, and
+ * `draw_polyline` / `draw_polygon` stroke a path of any width with the caps
+ * and joins of a `Stroke`.
+ *
+ * Segments reaching far off the image are clipped to it first, so the
+ * walks only step through pixels near the visible part.
+ */
+
+/**
+ * Shape of the open ends of a stroked path.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cap {
+    // ends exactly at the end point.
+    Butt,
+    // extends half the width past the end point.
+    Square,
+    // a half disc around the end point.
+    Round,
+}
+
+/**
+ * Shape of the outside corner where two segments of a path meet.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Join {
+    // the outer edges extended until they meet; corners sharper than
+    // `MITER_LIMIT` allows fall back to a bevel.
+    Miter,
+    // the outer corners connected by a straight edge.
+    Bevel,
+    // a disc around the corner.
+    Round,
+}
+
+/**
+ * Longest miter allowed, as a multiple of the line width: corners sharper
+ * than about 29 degrees are beveled, as in SVG.
+ */
+pub const MITER_LIMIT: f32 = 4.0;
+
+// anti-aliased strokes test a grid of AA_GRID x AA_GRID samples per pixel.
+const AA_GRID: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub cap: Cap,
+    pub join: Join,
+    // blend in the fraction of each pixel covered instead of drawing only
+    // the pixels whose center is covered.
+    pub antialiased: bool,
+}
+
+impl Stroke {
+    /**
+     * An anti-aliased stroke `width` pixels wide with butt caps and miter
+     * joins.
+     */
+    pub fn new(width: f32) -> Stroke {
+        Stroke {
+            width,
+            cap: Cap::Butt,
+            join: Join::Miter,
+            antialiased: true,
+        }
+    }
+}
+
+/**
+ * Visit the pixels of the Bresenham line from `start` to `end`, both
+ * included, along with how far along the line each one is, from 0 to 1.
+ */
+pub(crate) fn bresenham<F: FnMut(i64, i64, f32)>(start: (i64, i64), end: (i64, i64), mut visit: F) {
+    let (mut x, mut y) = start;
+    let (dx, dy) = ((end.0 - x).abs(), -(end.1 - y).abs());
+    let (sx, sy) = ((end.0 - x).signum(), (end.1 - y).signum());
+    let steps = dx.max(-dy).max(1) as f32;
+    // error of the next pixel, scaled by 1 / (dx * dy).
+    let mut err = dx + dy;
+    let mut step = 0;
+
+    loop {
+        visit(x, y, step as f32 / steps);
+        if (x, y) == end {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        step += 1;
+    }
+}
+
+/**
+ * Convex pieces whose union is a stroked path.
+ */
+enum Shape {
+    Polygon(Vec<Point>),
+    Disc(Point, f32),
+}
+
+impl Shape {
+    // points on the outline are inside.
+    fn contains(&self, p: Point) -> bool {
+        match *self {
+            Shape::Disc(center, radius) => length(p - center) <= radius,
+            Shape::Polygon(ref vertices) => {
+                let n = vertices.len();
+                // on the inner side of every edge, whichever way the
+                // polygon winds.
+                let orientation = signed_area(vertices).signum();
+                (0..n).all(|i| {
+                    let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+                    cross(b - a, p - a) * orientation >= 0.0
+                })
+            }
+        }
+    }
+
+    fn bbox(&self) -> (Point, Point) {
+        match *self {
+            Shape::Disc(center, radius) => (
+                Point {
+                    x: center.x - radius,
+                    y: center.y - radius,
+                },
+                Point {
+                    x: center.x + radius,
+                    y: center.y + radius,
+                },
+            ),
+            Shape::Polygon(ref vertices) => vertices.iter().fold(
+                (
+                    Point {
+                        x: f32::MAX,
+                        y: f32::MAX,
+                    },
+                    Point {
+                        x: f32::MIN,
+                        y: f32::MIN,
+                    },
+                ),
+                |(lo, hi), p| {
+                    (
+                        Point {
+                            x: lo.x.min(p.x),
+                            y: lo.y.min(p.y),
+                        },
+                        Point {
+                            x: hi.x.max(p.x),
+                            y: hi.y.max(p.y),
+                        },
+                    )
+                },
+            ),
+        }
+    }
+}
+
+fn dot(a: Point, b: Point) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Point, b: Point) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn length(a: Point) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn signed_area(vertices: &[Point]) -> f32 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| cross(vertices[i], vertices[(i + 1) % n]))
+        .sum::<f32>()
+        / 2.0
+}
+
+// the unit normal to the left of the direction `d`.
+fn left_normal(d: Point) -> Point {
+    Point { x: -d.y, y: d.x } * (1.0 / length(d))
+}
+
+/**
+ * Split a path into convex pieces: a rectangle per segment, plus its caps
+ * and joins. Repeated points are dropped first.
+ */
+fn stroke_shapes(points: &[Point], closed: bool, stroke: &Stroke) -> Vec<Shape> {
+    let mut path: Vec<Point> = Vec::with_capacity(points.len());
+    for &p in points.iter() {
+        if path.last() != Some(&p) {
+            path.push(p);
+        }
+    }
+    if closed && path.len() > 2 && path[0] == path[path.len() - 1] {
+        path.pop();
+    }
+
+    let half = stroke.width / 2.0;
+    let mut shapes = vec![];
+    if path.len() < 2 || half <= 0.0 {
+        return shapes;
+    }
+    let closed = closed && path.len() > 2;
+
+    let segments = if closed { path.len() } else { path.len() - 1 };
+    for i in 0..segments {
+        let (mut a, mut b) = (path[i], path[(i + 1) % path.len()]);
+        let direction = (b - a) * (1.0 / length(b - a));
+
+        if !closed && stroke.cap == Cap::Square {
+            if i == 0 {
+                a = a - direction * half;
+            }
+            if i == segments - 1 {
+                b = b + direction * half;
+            }
+        }
+        let n = left_normal(b - a) * half;
+        shapes.push(Shape::Polygon(vec![a + n, b + n, b - n, a - n]));
+    }
+
+    if !closed && stroke.cap == Cap::Round {
+        shapes.push(Shape::Disc(path[0], half));
+        shapes.push(Shape::Disc(path[path.len() - 1], half));
+    }
+
+    // corners: every inner point, and every point of a closed path.
+    let corners = if closed {
+        0..path.len()
+    } else {
+        1..path.len() - 1
+    };
+    for i in corners {
+        let prev = path[(i + path.len() - 1) % path.len()];
+        let (v, next) = (path[i], path[(i + 1) % path.len()]);
+        let (d0, d1) = (v - prev, next - v);
+
+        if stroke.join == Join::Round {
+            shapes.push(Shape::Disc(v, half));
+            continue;
+        }
+
+        let turn = cross(d0, d1);
+        if turn == 0.0 && dot(d0, d1) > 0.0 {
+            continue;
+        }
+        // the outer side of the corner is opposite to the turn.
+        let side = if turn > 0.0 { -half } else { half };
+        let (n0, n1) = (left_normal(d0), left_normal(d1));
+        let (p0, p1) = (v + n0 * side, v + n1 * side);
+
+        let bisector = n0 + n1;
+        let cos_half = dot(bisector, n0) / length(bisector);
+        if stroke.join == Join::Miter && length(bisector) > 0.0 && cos_half * MITER_LIMIT >= 1.0 {
+            let tip = v + bisector * (side / (length(bisector) * cos_half));
+            shapes.push(Shape::Polygon(vec![v, p0, tip, p1]));
+        } else if cross(p0 - v, p1 - v) != 0.0 {
+            // a path doubling back on itself has nothing to bevel.
+            shapes.push(Shape::Polygon(vec![v, p0, p1]));
+        }
+    }
+
+    shapes
+}
+
+impl ImageMeta {
+    fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64
+    }
+
+    fn pixel_index(&self, x: i64, y: i64) -> usize {
+        (self.height as usize - 1 - y as usize) * self.width as usize + x as usize
+    }
+
+    fn put(&mut self, x: i64, y: i64, color: &RGBPixel) {
+        if self.contains(x, y) {
+            let idx = self.pixel_index(x, y);
+            self.buffer[idx] = *color;
+        }
+    }
+
+    // mix `color` into the pixel at (x, y) by the fraction `coverage`.
+    fn blend(&mut self, x: i64, y: i64, color: &RGBPixel, coverage: f32) {
+        if !self.contains(x, y) || coverage <= 0.0 {
+            return;
+        }
+        let coverage = coverage.min(1.0);
+        let idx = self.pixel_index(x, y);
+        let p = &mut self.buffer[idx];
+        let mix =
+            |dst: u8, src: u8| (dst as f32 + (src as f32 - dst as f32) * coverage).round() as u8;
+
+        *p = RGBPixel {
+            red: mix(p.red, color.red),
+            green: mix(p.green, color.green),
+            blue: mix(p.blue, color.blue),
+        };
+    }
+
+    /**
+     * A one pixel line from pixel (x0, y0) to pixel (x1, y1), both drawn,
+     * using only integer arithmetic (Bresenham).
+     */
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &RGBPixel) {
+        let (mut start, mut end) = ((x0 as i64, y0 as i64), (x1 as i64, y1 as i64));
+
+        // lines within the image are drawn exactly as given; others restart
+        // on a one pixel border around it.
+        if !self.contains(start.0, start.1) || !self.contains(end.0, end.1) {
+            let max = (self.width as f64, self.height as f64);
+            let clipped = clip::clip_to_rect(
+                (x0 as f64, y0 as f64),
+                (x1 as f64, y1 as f64),
+                (-1.0, -1.0),
+                max,
+            );
+            let (a, b) = match clipped {
+                Some(segment) => segment,
+                None => return,
+            };
+            start = (a.0.round() as i64, a.1.round() as i64);
+            end = (b.0.round() as i64, b.1.round() as i64);
+        }
+
+        bresenham(start, end, |x, y, _| self.put(x, y, color));
+    }
+
+    /**
+     * An anti-aliased one pixel wide line between two points in image space
+     * (Xiaolin Wu): each step along the major axis blends the color into the
+     * two pixels straddling the line, in proportion to how close their
+     * centers are to it.
+     */
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &RGBPixel) {
+        // the clipped ends stay two pixels off the image, where their partial
+        // coverage is not seen.
+        let (w, h) = (self.width as f64, self.height as f64);
+        let clipped = clip::clip_to_rect(
+            (x0 as f64, y0 as f64),
+            (x1 as f64, y1 as f64),
+            (-2.0, -2.0),
+            (w + 2.0, h + 2.0),
+        );
+        let (x0, y0, x1, y1) = match clipped {
+            Some((a, b)) => (a.0 as f32, a.1 as f32, b.0 as f32, b.1 as f32),
+            None => return,
+        };
+
+        // move pixel centers onto integer coordinates.
+        let (mut x0, mut y0, mut x1, mut y1) = (x0 - 0.5, y0 - 0.5, x1 - 0.5, y1 - 0.5);
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        let plot = |image: &mut ImageMeta, major: i64, minor: i64, coverage: f32| {
+            if steep {
+                image.blend(minor, major, color, coverage);
+            } else {
+                image.blend(major, minor, color, coverage);
+            }
+        };
+        let fpart = |v: f32| v - v.floor();
+
+        // the end points cover only part of their pixel along the major
+        // axis, `gap`.
+        let end_point = |image: &mut ImageMeta, x: f32, y: f32, gap: f32| {
+            let major = (x + 0.5).floor();
+            let y = y + gradient * (major - x);
+
+            plot(
+                image,
+                major as i64,
+                y.floor() as i64,
+                (1.0 - fpart(y)) * gap,
+            );
+            plot(image, major as i64, y.floor() as i64 + 1, fpart(y) * gap);
+            major
+        };
+        let first = end_point(self, x0, y0, 1.0 - fpart(x0 + 0.5));
+        let last = end_point(self, x1, y1, fpart(x1 + 0.5));
+
+        let mut y = y0 + gradient * (first + 1.0 - x0);
+        for major in first as i64 + 1..last as i64 {
+            let minor = y.floor() as i64;
+            plot(self, major, minor, 1.0 - fpart(y));
+            plot(self, major, minor + 1, fpart(y));
+            y += gradient;
+        }
+    }
+
+    /**
+     * Stroke the open path through `points` in image space. Overlapping
+     * segments, caps and joins are merged, so every pixel is blended once.
+     */
+    pub fn draw_polyline(&mut self, points: &[Point], stroke: &Stroke, color: &RGBPixel) {
+        self.draw_path(points, false, stroke, color);
+    }
+
+    /**
+     * Stroke the closed outline of the polygon `points`, joining the last
+     * point back to the first.
+     */
+    pub fn draw_polygon(&mut self, points: &[Point], stroke: &Stroke, color: &RGBPixel) {
+        self.draw_path(points, true, stroke, color);
+    }
+
+    fn draw_path(&mut self, points: &[Point], closed: bool, stroke: &Stroke, color: &RGBPixel) {
+        let shapes = stroke_shapes(points, closed, stroke);
+        if shapes.is_empty() {
+            return;
+        }
+
+        // the pixels the path can touch, on the image.
+        let (lo, hi) = shapes.iter().map(Shape::bbox).fold(
+            (
+                Point {
+                    x: f32::MAX,
+                    y: f32::MAX,
+                },
+                Point {
+                    x: f32::MIN,
+                    y: f32::MIN,
+                },
+            ),
+            |(lo, hi), (a, b)| {
+                (
+                    Point {
+                        x: lo.x.min(a.x),
+                        y: lo.y.min(a.y),
+                    },
+                    Point {
+                        x: hi.x.max(b.x),
+                        y: hi.y.max(b.y),
+                    },
+                )
+            },
+        );
+        let x_min = (lo.x.floor() as i64 - 1).max(0);
+        let y_min = (lo.y.floor() as i64 - 1).max(0);
+        let x_max = (hi.x.ceil() as i64 + 1).min(self.width as i64 - 1);
+        let y_max = (hi.y.ceil() as i64 + 1).min(self.height as i64 - 1);
+        if x_min > x_max || y_min > y_max {
+            return;
+        }
+
+        // a bit per sample of every pixel, set when any piece covers it;
+        // the coverage of the union is the fraction of samples set.
+        let samples: Vec<Point> = if stroke.antialiased {
+            (0..AA_GRID * AA_GRID)
+                .map(|i| Point {
+                    x: ((i % AA_GRID) as f32 + 0.5) / AA_GRID as f32,
+                    y: ((i / AA_GRID) as f32 + 0.5) / AA_GRID as f32,
+                })
+                .collect()
+        } else {
+            vec![Point { x: 0.5, y: 0.5 }]
+        };
+        let columns = (x_max - x_min + 1) as usize;
+        let mut masks = vec![0_u32; columns * (y_max - y_min + 1) as usize];
+
+        for shape in shapes.iter() {
+            let (a, b) = shape.bbox();
+            let (x0, x1) = (
+                (a.x.floor() as i64).max(x_min),
+                (b.x.ceil() as i64).min(x_max),
+            );
+            let (y0, y1) = (
+                (a.y.floor() as i64).max(y_min),
+                (b.y.ceil() as i64).min(y_max),
+            );
+
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let idx = (y - y_min) as usize * columns + (x - x_min) as usize;
+                    let corner = Point {
+                        x: x as f32,
+                        y: y as f32,
+                    };
+
+                    for (i, &sample) in samples.iter().enumerate() {
+                        if shape.contains(corner + sample) {
+                            masks[idx] |= 1 << i;
+                        }
+                    }
+                }
+            }
+        }
+
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                let mask = masks[(y - y_min) as usize * columns + (x - x_min) as usize];
+                let c = mask.count_ones() as f32 / samples.len() as f32;
+                self.blend(x, y, color, c);
+            }
+        }
+    }
+}
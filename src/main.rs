@@ -1,132 +1,474 @@
 extern crate tiny_renderer;
 
-use tiny_renderer::{matrix::Matrix3x3, ImageMeta, Point3D, RGBPixel, Triangle};
+use tiny_renderer::shading::{
+    FlatShader, GouraudShader, NormalMap, PhongShader, Specular, TextureShader,
+};
+use tiny_renderer::shadow::ShadowMap;
+use tiny_renderer::{
+    Camera, Error, Filter, Format, ImageMeta, Mesh, Multisample, Point3D, Projection, RGBPixel,
+    SceneFile, Texture, TextureFilter, Wireframe, WireframeMode,
+};
 
-use std::f32;
-use std::path::Path;
-use std::vec::Vec;
-use tiny_renderer::imagefmt::{ColFmt, Image};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::thread;
 
-fn render_with_texture_projection(
-    obj_filename: &str,
-    tga_filename: &str,
+const USAGE: &str = "\
+usage: tiny_renderer [options] [--] MESH
+       tiny_renderer [--threads N] --scene PATH
+       tiny_renderer -h | --help
+
+Render the OBJ file MESH, or the scene file PATH, which sets everything
+itself (see the scene_file module of the library for its format).
+
+options:
+  -o, --output PATH        image to write (default: out.tga)
+  -f, --format FORMAT      png, tga, ppm or pgm (default: from the output extension)
+  -w, --width N            image width in pixels (default: 800)
+  -H, --height N           image height in pixels (default: 800)
+  -s, --shading MODE       wireframe, hidden-line, flat, gouraud, textured or phong
+                           (default: phong)
+  -t, --texture PATH       diffuse texture for textured or phong shading, required
+                           by textured shading
+  -n, --normal-map PATH    tangent space normal map, phong shading only
+      --filter MODE        filtering of the texture and normal map: nearest,
+                           bilinear, trilinear or anisotropic (default: trilinear)
+      --shadows            cast shadows, phong shading only
+      --wireframe          outline the visible edges over the shaded mesh, not with
+                           wireframe or hidden-line shading
+      --eye X,Y,Z          camera position (default: 0,0,3)
+      --target X,Y,Z       point the camera looks at (default: 0,0,0)
+      --up X,Y,Z           camera up direction (default: 0,1,0)
+      --fov DEGREES        vertical field of view, between 0 and 180 (default: 45)
+      --light X,Y,Z        direction the light travels in (default: 0,0,-1)
+      --threads N          rasterizer threads (default: one per CPU)
+      --msaa N             samples per pixel: 1, 2, 4 or 8 (default: 1), for the
+                           shaded modes only
+      --ssaa N             render N times larger and filter down (default: 1)
+      --scene PATH         render a scene file instead of a single mesh
+  -h, --help               print this message";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Shading {
+    Wireframe,
+    HiddenLine,
+    Flat,
+    Gouraud,
+    Textured,
+    Phong,
+}
+
+struct Options {
+    // -h or --help was given; nothing else is set.
+    help: bool,
+    mesh: PathBuf,
+    scene: Option<PathBuf>,
+    output: PathBuf,
+    format: Option<Format>,
     width: usize,
     height: usize,
-) {
-    // dimensions of image
-    let buffer: Vec<RGBPixel> = vec![
-        RGBPixel {
-            red: 0,
-            green: 0,
-            blue: 0
-        };
-        width * height
-    ];
-    let mut zbuffer: Vec<f32> = vec![f32::MIN; width * height];
-    let mut black = ImageMeta {
-        buffer: buffer,
-        width: width as i32,
-        height: height as i32,
-    };
+    shading: Shading,
+    texture: Option<PathBuf>,
+    normal_map: Option<PathBuf>,
+    filter: TextureFilter,
+    shadows: bool,
+    wireframe: bool,
+    eye: Point3D,
+    target: Point3D,
+    up: Point3D,
+    fov: f32,
+    light: Point3D,
+    threads: usize,
+    msaa: Multisample,
+    ssaa: usize,
+}
 
-    let texture_img: Image<u8> =
-        imagefmt::read("./data/african_head_diffuse.tga", ColFmt::Auto).unwrap();
+fn parse_shading(value: &str) -> Result<Shading, String> {
+    match value {
+        "wireframe" => Ok(Shading::Wireframe),
+        "hidden-line" => Ok(Shading::HiddenLine),
+        "flat" => Ok(Shading::Flat),
+        "gouraud" => Ok(Shading::Gouraud),
+        "textured" => Ok(Shading::Textured),
+        "phong" => Ok(Shading::Phong),
+        _ => Err(format!("unknown shading mode '{}'", value)),
+    }
+}
+
+fn parse_filter(value: &str) -> Result<TextureFilter, String> {
+    match value {
+        "nearest" => Ok(TextureFilter::Nearest),
+        "bilinear" => Ok(TextureFilter::Bilinear),
+        "trilinear" => Ok(TextureFilter::Trilinear),
+        "anisotropic" => Ok(TextureFilter::Anisotropic(8)),
+        _ => Err(format!("unknown texture filter '{}'", value)),
+    }
+}
 
-    let head = tobj::load_obj(&Path::new(obj_filename));
-    assert!(head.is_ok());
-    let (models, _) = head.unwrap();
+fn parse_multisample(value: &str) -> Result<Multisample, String> {
+    match value {
+        "1" => Ok(Multisample::Off),
+        "2" => Ok(Multisample::X2),
+        "4" => Ok(Multisample::X4),
+        "8" => Ok(Multisample::X8),
+        _ => Err(format!("--msaa: expected 1, 2, 4 or 8, found '{}'", value)),
+    }
+}
 
-    let mesh = &models[0].mesh;
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{}: '{}' is not a number", option, value))
+}
 
-    let image_position = |pos: f32| (width as f32) * (pos + 1.0) / 2.0;
+// a vector given as three comma separated numbers, e.g. "0,1.5,-2".
+fn parse_vector(option: &str, value: &str) -> Result<Point3D, String> {
+    let coords = value
+        .split(',')
+        .map(|c| parse_number::<f32>(option, c.trim()))
+        .collect::<Result<Vec<f32>, String>>()?;
+
+    if coords.len() != 3 {
+        return Err(format!("{}: expected X,Y,Z, found '{}'", option, value));
+    }
+    Ok(Point3D::new([coords[0], coords[1], coords[2]]))
+}
 
-    let point3d_image_position = |p: Point3D| Point3D {
-        x: image_position(p.x),
-        y: image_position(p.y),
-        z: image_position(p.z),
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut mesh = None;
+    let mut options = Options {
+        help: false,
+        mesh: PathBuf::new(),
+        scene: None,
+        output: PathBuf::from("out.tga"),
+        format: None,
+        width: 800,
+        height: 800,
+        shading: Shading::Phong,
+        texture: None,
+        normal_map: None,
+        filter: TextureFilter::Trilinear,
+        shadows: false,
+        wireframe: false,
+        eye: Point3D::new([0.0, 0.0, 3.0]),
+        target: Point3D::zero(),
+        up: Point3D::new([0.0, 1.0, 0.0]),
+        fov: 45.0,
+        light: Point3D::new([0.0, 0.0, -1.0]),
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        msaa: Multisample::Off,
+        ssaa: 1,
     };
 
-    /*
-     *   Projection matrix
-     *   [ (c-1)/(c-z)     0      0  0 ]     [x]
-     *   [      0     (c-1)/(c-z) 0  0 ]  *  [y]
-     *   [      0          0      1  0 ]     [z]
-     *   [      0          0      0  1 ]     [1]
-     */
-    let c = 5.0;
-    let mut projection_matrix = Matrix3x3::identity();
-
-    let mut triangle: Triangle = Triangle {
-        vertices: [Point3D::zero(), Point3D::zero(), Point3D::zero()],
+    // options given other than --threads and --scene, in order.
+    let mut given = vec![];
+    // set by "--", after which every argument is the mesh.
+    let mut options_done = false;
+
+    while let Some(arg) = args.next() {
+        if options_done || !arg.starts_with('-') {
+            if mesh.is_some() {
+                return Err(format!("unexpected argument '{}'", arg));
+            }
+            mesh = Some(PathBuf::from(arg));
+            continue;
+        }
+        match arg.as_str() {
+            "--" => {
+                options_done = true;
+                continue;
+            }
+            "-h" | "--help" => {
+                options.help = true;
+                return Ok(options);
+            }
+            "--threads" | "--scene" => {}
+            _ => given.push(arg.clone()),
+        }
+        if arg == "--shadows" {
+            options.shadows = true;
+            continue;
+        }
+        if arg == "--wireframe" {
+            options.wireframe = true;
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+
+        match arg.as_str() {
+            "-o" | "--output" => options.output = PathBuf::from(value),
+            "-f" | "--format" => match Format::from_extension(&value) {
+                Some(format) => options.format = Some(format),
+                None => return Err(format!("unknown image format '{}'", value)),
+            },
+            "-w" | "--width" => options.width = parse_number(&arg, &value)?,
+            "-H" | "--height" => options.height = parse_number(&arg, &value)?,
+            "-s" | "--shading" => options.shading = parse_shading(&value)?,
+            "-t" | "--texture" => options.texture = Some(PathBuf::from(value)),
+            "-n" | "--normal-map" => options.normal_map = Some(PathBuf::from(value)),
+            "--filter" => options.filter = parse_filter(&value)?,
+            "--eye" => options.eye = parse_vector(&arg, &value)?,
+            "--target" => options.target = parse_vector(&arg, &value)?,
+            "--up" => options.up = parse_vector(&arg, &value)?,
+            "--fov" => options.fov = parse_number(&arg, &value)?,
+            "--light" => options.light = parse_vector(&arg, &value)?,
+            "--threads" => options.threads = parse_number(&arg, &value)?,
+            "--msaa" => options.msaa = parse_multisample(&value)?,
+            "--ssaa" => options.ssaa = parse_number(&arg, &value)?,
+            "--scene" => options.scene = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    if options.scene.is_some() {
+        if mesh.is_some() {
+            return Err("give either a MESH or --scene, not both".to_string());
+        }
+        if let Some(option) = given.first() {
+            return Err(format!(
+                "{} cannot be used with --scene, the scene file sets it",
+                option
+            ));
+        }
+        return Ok(options);
+    }
+    options.mesh = match mesh {
+        Some(mesh) => mesh,
+        None => return Err("no mesh given".to_string()),
     };
-    let mut triangle_world_coords = vec![Point3D::zero(); 3];
-    let light_dir = Point3D {
-        x: 0.0,
-        y: 0.0,
-        z: -1.0,
+    if options.format.is_none() && Format::from_path(&options.output).is_none() {
+        return Err(format!(
+            "cannot tell the format of '{}', pass --format",
+            options.output.display()
+        ));
+    }
+    if options.ssaa == 0 {
+        return Err("--ssaa: the factor must be at least 1".to_string());
+    }
+    if !(options.fov > 0.0 && options.fov < 180.0) {
+        return Err(format!(
+            "--fov: expected an angle between 0 and 180, found {}",
+            options.fov
+        ));
+    }
+    if camera(&options).is_degenerate() {
+        return Err(
+            "the eye must differ from the target and --up must not point along the view"
+                .to_string(),
+        );
+    }
+
+    let given_count = |names: &[&str]| {
+        given
+            .iter()
+            .filter(|&arg| names.contains(&arg.as_str()))
+            .count()
     };
+    if given_count(&["-s", "--shading"]) > 1 {
+        return Err("--shading given more than once".to_string());
+    }
+    if options.shading == Shading::Textured && options.texture.is_none() {
+        return Err("textured shading needs a --texture".to_string());
+    }
+    if options.texture.is_some() && !matches!(options.shading, Shading::Textured | Shading::Phong) {
+        return Err("--texture needs textured or phong shading".to_string());
+    }
+    if options.shading != Shading::Phong {
+        if options.shadows {
+            return Err("--shadows needs phong shading".to_string());
+        }
+        if options.normal_map.is_some() {
+            return Err("--normal-map needs phong shading".to_string());
+        }
+    }
+    if given_count(&["--filter"]) > 0 && options.texture.is_none() && options.normal_map.is_none() {
+        return Err("--filter needs a --texture or --normal-map".to_string());
+    }
+    if matches!(options.shading, Shading::Wireframe | Shading::HiddenLine) {
+        if options.wireframe {
+            return Err("--wireframe needs a shaded mode, the edges are drawn already".to_string());
+        }
+        if options.msaa != Multisample::Off {
+            return Err("--msaa needs a shaded mode, use --ssaa to smooth lines".to_string());
+        }
+    }
 
-    let mut texcoords = [Point3D::zero(); 3];
+    Ok(options)
+}
 
-    for f in 0..mesh.indices.len() / 3 {
-        for j in 0..3 {
-            let v = mesh.indices[3 * f + j] as usize;
+fn camera(options: &Options) -> Camera {
+    Camera::look_at(
+        options.eye,
+        options.target,
+        options.up,
+        Projection::Perspective {
+            fovy: options.fov.to_radians(),
+            aspect: options.width as f32 / options.height as f32,
+            near: 0.1,
+            far: 100.0,
+        },
+    )
+}
 
-            projection_matrix.set(0, 0, (c - 1.0) / (c - mesh.positions[v * 3 + 2]));
-            projection_matrix.set(1, 1, (c - 1.0) / (c - mesh.positions[v * 3 + 2]));
+fn render(options: &Options) -> Result<(), Error> {
+    let (meshes, _) = Mesh::load_obj(&options.mesh)?;
+    let mut image = ImageMeta::new(
+        options.width.saturating_mul(options.ssaa),
+        options.height.saturating_mul(options.ssaa),
+    )?;
+    image.threads = options.threads;
+    image.multisample = options.msaa;
 
-            texcoords[j] = Point3D {
-                x: mesh.texcoords[v * 2] * (texture_img.w as f32),
-                y: mesh.texcoords[v * 2 + 1] * (texture_img.h as f32),
-                z: 0.0,
-            };
+    let camera = camera(options);
+    let mut light_dir = options.light;
+    light_dir.normalize();
+
+    let white = RGBPixel {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+    let texture = match options.texture {
+        Some(ref path) => Some(Texture::load_with_filter(path, options.filter)?),
+        None => None,
+    };
 
-            // screen coords
-            triangle.vertices[j] = point3d_image_position(
-                projection_matrix
-                    * Point3D {
-                        x: mesh.positions[v * 3],
-                        y: mesh.positions[v * 3 + 1],
-                        z: mesh.positions[v * 3 + 2],
-                    },
-            );
-
-            triangle_world_coords[j] = Point3D {
-                x: mesh.positions[v * 3],
-                y: mesh.positions[v * 3 + 1],
-                z: mesh.positions[v * 3 + 2],
+    match options.shading {
+        Shading::Wireframe | Shading::HiddenLine => {
+            let mode = if options.shading == Shading::Wireframe {
+                WireframeMode::All
+            } else {
+                WireframeMode::HiddenLine
+            };
+            let wireframe = Wireframe::new(&camera, white, mode);
+            for mesh in meshes.iter() {
+                image.draw_wireframe(mesh, &wireframe);
+            }
+        }
+        Shading::Flat => {
+            let mut shader = FlatShader {
+                transform: camera.view_projection_matrix(),
+                light_dir,
+                color: white,
+            };
+            for mesh in meshes.iter() {
+                image.draw_mesh(mesh, &mut shader);
+            }
+        }
+        Shading::Gouraud => {
+            let mut shader = GouraudShader {
+                transform: camera.view_projection_matrix(),
+                light_dir,
+                color: white,
             };
+            for mesh in meshes.iter() {
+                image.draw_mesh(mesh, &mut shader);
+            }
         }
+        Shading::Textured => {
+            let mut shader = TextureShader {
+                transform: camera.view_projection_matrix(),
+                light_dir,
+                texture: texture.expect("checked by parse_args"),
+            };
+            for mesh in meshes.iter() {
+                mesh.require_texcoords()?;
+                image.draw_mesh(mesh, &mut shader);
+            }
+        }
+        Shading::Phong => {
+            let mut shader = PhongShader::new(&camera, light_dir);
+            shader.specular = Specular::BlinnPhong;
+            shader.texture = texture.map(Arc::new);
 
-        let mut xprod = (triangle_world_coords[2] - triangle_world_coords[0])
-            * (triangle_world_coords[1] - triangle_world_coords[0]);
-        xprod.normalize();
+            if let Some(ref path) = options.normal_map {
+                let map = Texture::load_with_filter(path, options.filter)?;
+                shader.normal_map = Some(Arc::new(NormalMap::TangentSpace(map)));
+            }
+            if options.shadows {
+                shader.shadow = Some(ShadowMap::render(
+                    &ShadowMap::light_camera(&meshes, light_dir),
+                    &meshes,
+                    1024,
+                    1024,
+                )?);
+            }
+            for mesh in meshes.iter() {
+                if shader.texture.is_some() || shader.normal_map.is_some() {
+                    mesh.require_texcoords()?;
+                }
+                image.draw_mesh(mesh, &mut shader);
+            }
+        }
+    }
 
-        // light emanates from (0,0,0) an strikes the triangle in the bary center.
-        //  let light_dir_length = light_dir.length(); * (1.0 / light_dir_length)
-        //light_dir.normalize();
-        let intensity = xprod.dot(light_dir);
-        if intensity > 0.0 {
-            black.draw_triangle_with_texture(
-                triangle,
-                texcoords,
-                &texture_img,
-                intensity,
-                &mut zbuffer,
-            );
+    // parse_args only allows --wireframe with the shaded modes.
+    if options.wireframe {
+        let black = RGBPixel {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        let wireframe = Wireframe::new(&camera, black, WireframeMode::Overlay);
+        for mesh in meshes.iter() {
+            image.draw_wireframe(mesh, &wireframe);
         }
     }
-    black.to_tga_image(tga_filename);
+
+    if options.ssaa > 1 {
+        image = image.downsample(options.ssaa, Filter::Tent)?;
+    }
+
+    let format = options
+        .format
+        .or_else(|| Format::from_path(&options.output))
+        .expect("checked by parse_args");
+    image.save_as(&options.output, format)
+}
+
+/**
+ * Render a scene file and return the path of the image written.
+ */
+fn render_scene(path: &Path, threads: usize) -> Result<PathBuf, Error> {
+    let mut file = SceneFile::load(path)?;
+    file.output.threads = threads;
+
+    let image = file.render()?;
+    image.save_as(&file.output.path, file.output.format)?;
+    Ok(file.output.path)
 }
 
 fn main() {
-    render_with_texture_projection(
-        "./data/african_head.obj",
-        "african_head_with_texture_ortho.tga",
-        500,
-        500,
-    );
-    //draw_triangle("triangles.tga", 500, 500);
-    //draw_triangles_line_sweep("triangles_line_sweep.tga", 500,500)
-    //barycentric_coords_test();
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let written = match options.scene {
+        Some(ref scene) => render_scene(scene, options.threads)
+            .map_err(|err| format!("{}: {}", scene.display(), err)),
+        None => render(&options)
+            .map(|_| options.output.clone())
+            .map_err(|err| err.to_string()),
+    };
+    match written {
+        Ok(path) => println!("image written to {}", path.display()),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            process::exit(1);
+        }
+    }
 }
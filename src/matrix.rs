@@ -1,8 +1,9 @@
+use error::Error;
 use geometry::Point3D;
 use std::cmp::{Eq, PartialEq};
 use std::f32;
 use std::fmt;
-use std::ops::Mul;
+use std::ops::{Mul, Neg};
 use std::string::String;
 //use typenum::UInt;
 
@@ -26,7 +27,22 @@ impl fmt::Debug for Matrix3x3 {
             s.push_str("[ ");
             for idy in 0..3 {
                 s.push_str(&self.get(idx, idy).to_string());
-                s.push_str(" ");
+                s.push(' ');
+            }
+            s.push_str("]\n");
+        }
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Debug for Matrix4x4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::from("");
+        for idx in 0..4 {
+            s.push_str("[ ");
+            for idy in 0..4 {
+                s.push_str(&self.get(idx, idy).to_string());
+                s.push(' ');
             }
             s.push_str("]\n");
         }
@@ -50,6 +66,12 @@ impl Eq for Matrix4x4 {}
 
 impl Eq for Matrix3x3 {}
 
+impl Default for Matrix3x3 {
+    fn default() -> Matrix3x3 {
+        Matrix3x3::new()
+    }
+}
+
 impl Matrix3x3 {
     pub fn new() -> Matrix3x3 {
         Matrix3x3 { buffer: [0.0; 9] }
@@ -67,11 +89,57 @@ impl Matrix3x3 {
     }
 
     // column major indexing
-    pub fn set(&mut self, i: usize, j: usize, val: f32) -> () {
+    pub fn set(&mut self, i: usize, j: usize, val: f32) {
         self.buffer[i + 3 * j] = val;
     }
 }
 
+impl Mul<Matrix3x3> for Matrix3x3 {
+    type Output = Matrix3x3;
+
+    fn mul(self, other: Matrix3x3) -> Matrix3x3 {
+        let mut mat = Matrix3x3::new();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let mut ret = 0.0;
+                for c in 0..3 {
+                    ret += self.get(i, c) * other.get(c, j);
+                }
+                mat.set(i, j, ret);
+            }
+        }
+
+        mat
+    }
+}
+
+// the upper left 3x3 block: the linear part of an affine transform.
+impl From<Matrix4x4> for Matrix3x3 {
+    fn from(mat: Matrix4x4) -> Matrix3x3 {
+        let mut ret = Matrix3x3::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                ret.set(i, j, mat.get(i, j));
+            }
+        }
+        ret
+    }
+}
+
+// the linear transform as a 4x4 matrix, without translation.
+impl From<Matrix3x3> for Matrix4x4 {
+    fn from(mat: Matrix3x3) -> Matrix4x4 {
+        let mut ret = Matrix4x4::identity();
+        for i in 0..3 {
+            for j in 0..3 {
+                ret.set(i, j, mat.get(i, j));
+            }
+        }
+        ret
+    }
+}
+
 impl Mul<Point3D> for Matrix3x3 {
     type Output = Point3D;
 
@@ -103,9 +171,132 @@ impl Matrix4x4 {
     }
 
     // column major indexing
-    pub fn set(&mut self, i: usize, j: usize, val: f32) -> () {
+    pub fn set(&mut self, i: usize, j: usize, val: f32) {
         self.buffer[j + 4 * i] = val;
     }
+
+    /**
+     * Move points by `offset`; directions (w = 0) are left alone.
+     */
+    pub fn translation(offset: Point3D) -> Matrix4x4 {
+        let mut mat = Matrix4x4::identity();
+        mat.set(0, 3, offset.x);
+        mat.set(1, 3, offset.y);
+        mat.set(2, 3, offset.z);
+        mat
+    }
+
+    /**
+     * Scale each axis by the matching coordinate of `factors`.
+     */
+    pub fn scale(factors: Point3D) -> Matrix4x4 {
+        let mut mat = Matrix4x4::identity();
+        mat.set(0, 0, factors.x);
+        mat.set(1, 1, factors.y);
+        mat.set(2, 2, factors.z);
+        mat
+    }
+
+    /**
+     * Rotate by `angle` radians about `axis`, which need not be of unit
+     * length. Positive angles turn counter-clockwise when looking down the
+     * axis towards the origin.
+     *
+     *   With c = cos(angle), s = sin(angle) and the unit axis (x, y, z):
+     *
+     *   [ c + x*x(1-c)    x*y(1-c) - z*s  x*z(1-c) + y*s  0 ]
+     *   [ y*x(1-c) + z*s  c + y*y(1-c)    y*z(1-c) - x*s  0 ]
+     *   [ z*x(1-c) - y*s  z*y(1-c) + x*s  c + z*z(1-c)    0 ]
+     *   [ 0               0               0               1 ]
+     */
+    pub fn rotation(axis: Point3D, angle: f32) -> Matrix4x4 {
+        let mut a = axis;
+        a.normalize();
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+
+        let mut mat = Matrix4x4::identity();
+        mat.set(0, 0, c + a.x * a.x * t);
+        mat.set(0, 1, a.x * a.y * t - a.z * s);
+        mat.set(0, 2, a.x * a.z * t + a.y * s);
+        mat.set(1, 0, a.y * a.x * t + a.z * s);
+        mat.set(1, 1, c + a.y * a.y * t);
+        mat.set(1, 2, a.y * a.z * t - a.x * s);
+        mat.set(2, 0, a.z * a.x * t - a.y * s);
+        mat.set(2, 1, a.z * a.y * t + a.x * s);
+        mat.set(2, 2, c + a.z * a.z * t);
+        mat
+    }
+
+    pub fn transpose(&self) -> Matrix4x4 {
+        let mut mat = Matrix4x4::zero();
+        for i in 0..4 {
+            for j in 0..4 {
+                mat.set(i, j, self.get(j, i));
+            }
+        }
+        mat
+    }
+
+    // the determinant of the 3x3 matrix left after removing row `i` and
+    // column `j`, signed by the position of (i, j).
+    fn cofactor(&self, i: usize, j: usize) -> f32 {
+        let mut m = [0.0; 9];
+        let mut k = 0;
+        for row in (0..4).filter(|&row| row != i) {
+            for col in (0..4).filter(|&col| col != j) {
+                m[k] = self.get(row, col);
+                k += 1;
+            }
+        }
+
+        let minor = m[0] * (m[4] * m[8] - m[5] * m[7]) - m[1] * (m[3] * m[8] - m[5] * m[6])
+            + m[2] * (m[3] * m[7] - m[4] * m[6]);
+        if (i + j).is_multiple_of(2) {
+            minor
+        } else {
+            -minor
+        }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        (0..4).map(|j| self.get(0, j) * self.cofactor(0, j)).sum()
+    }
+
+    /**
+     * The matrix undoing this one, from its cofactors. Fails with
+     * `Error::SingularMatrix` when the determinant is zero (or not finite),
+     * e.g. for a scale by zero or a projection.
+     */
+    pub fn inverse(&self) -> Result<Matrix4x4, Error> {
+        let mut adjugate = Matrix4x4::zero();
+        for i in 0..4 {
+            for j in 0..4 {
+                adjugate.set(j, i, self.cofactor(i, j));
+            }
+        }
+
+        // expanding along the first row again reuses the cofactors.
+        let det: f32 = (0..4).map(|j| self.get(0, j) * adjugate.get(j, 0)).sum();
+        if det == 0.0 || !det.is_finite() {
+            return Err(Error::SingularMatrix);
+        }
+
+        for value in adjugate.buffer.iter_mut() {
+            *value /= det;
+        }
+        Ok(adjugate)
+    }
+
+    /**
+     * The transpose of the inverse, which takes the normals of a surface
+     * transformed by this matrix to the normals of the transformed surface
+     * (w = 0, renormalized afterwards). For rotations it is the matrix
+     * itself.
+     */
+    pub fn inverse_transpose(&self) -> Result<Matrix4x4, Error> {
+        Ok(self.inverse()?.transpose())
+    }
 }
 
 impl Mul<Matrix4x4> for Matrix4x4 {
@@ -177,3 +368,212 @@ impl Mul<[f32; 4]> for &Matrix4x4 {
         ]
     }
 }
+
+/**
+ * A rotation as a unit quaternion w + xi + yj + zk. Rotating by `angle`
+ * about the unit axis a gives w = cos(angle / 2) and (x, y, z) =
+ * sin(angle / 2) * a, so q and -q are the same rotation.
+ *
+ * Unlike matrices, quaternions interpolate well: `slerp` turns at a constant
+ * rate between two orientations along the shortest arc, e.g. between the
+ * keyframes of an animated object or camera.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quaternion {
+    pub fn identity() -> Quaternion {
+        Quaternion {
+            w: 1.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+
+    /**
+     * Rotate by `angle` radians about `axis`, like `Matrix4x4::rotation`.
+     */
+    pub fn from_axis_angle(axis: Point3D, angle: f32) -> Quaternion {
+        let mut a = axis;
+        a.normalize();
+        let (s, c) = (angle / 2.0).sin_cos();
+
+        Quaternion {
+            w: c,
+            x: a.x * s,
+            y: a.y * s,
+            z: a.z * s,
+        }
+    }
+
+    /**
+     * Rotate by `x` radians about the x axis, then by `y` about the y axis
+     * and last by `z` about the z axis.
+     */
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Quaternion {
+        let axis = |x, y, z| Point3D::new([x, y, z]);
+
+        Quaternion::from_axis_angle(axis(0.0, 0.0, 1.0), z)
+            * Quaternion::from_axis_angle(axis(0.0, 1.0, 0.0), y)
+            * Quaternion::from_axis_angle(axis(1.0, 0.0, 0.0), x)
+    }
+
+    pub fn dot(self, other: Quaternion) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /**
+     * The unit quaternion in the same direction; products of unit
+     * quaternions drift away from length 1 as rounding errors pile up.
+     */
+    pub fn normalized(self) -> Quaternion {
+        self.scale(1.0 / self.length())
+    }
+
+    /**
+     * The opposite rotation, for a unit quaternion.
+     */
+    pub fn conjugate(self) -> Quaternion {
+        Quaternion {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Quaternion {
+        Quaternion {
+            w: self.w * factor,
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+
+    fn add(self, other: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w + other.w,
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+
+    /**
+     * Rotate `p` about the origin.
+     */
+    pub fn rotate(self, p: Point3D) -> Point3D {
+        // p + 2w(v x p) + 2v x (v x p), with v = (x, y, z); cheaper than
+        // the two quaternion products of q p q*.
+        let v = Point3D::new([self.x, self.y, self.z]);
+        let t = 2.0 * (v * p);
+        p + self.w * t + v * t
+    }
+
+    /**
+     * The rotation as a matrix.
+     */
+    pub fn to_matrix(self) -> Matrix4x4 {
+        let Quaternion { w, x, y, z } = self;
+
+        let mut mat = Matrix4x4::identity();
+        mat.set(0, 0, 1.0 - 2.0 * (y * y + z * z));
+        mat.set(0, 1, 2.0 * (x * y - w * z));
+        mat.set(0, 2, 2.0 * (x * z + w * y));
+        mat.set(1, 0, 2.0 * (x * y + w * z));
+        mat.set(1, 1, 1.0 - 2.0 * (x * x + z * z));
+        mat.set(1, 2, 2.0 * (y * z - w * x));
+        mat.set(2, 0, 2.0 * (x * z - w * y));
+        mat.set(2, 1, 2.0 * (y * z + w * x));
+        mat.set(2, 2, 1.0 - 2.0 * (x * x + y * y));
+        mat
+    }
+
+    // `other`, or `-other` when that is closer to `self`: both are the
+    // same rotation, and interpolating towards the closer one takes the
+    // short way round.
+    fn nearest(self, other: Quaternion) -> Quaternion {
+        if self.dot(other) < 0.0 {
+            -other
+        } else {
+            other
+        }
+    }
+
+    /**
+     * Normalized linear interpolation: `self` at t = 0 and `other` at
+     * t = 1. Cheaper than `slerp` and as smooth, but it turns faster in
+     * the middle than at the ends.
+     */
+    pub fn nlerp(self, other: Quaternion, t: f32) -> Quaternion {
+        let other = self.nearest(other);
+        self.scale(1.0 - t).add(other.scale(t)).normalized()
+    }
+
+    /**
+     * Spherical linear interpolation: `self` at t = 0 and `other` at t = 1,
+     * turning at a constant rate along the shortest arc between them.
+     */
+    pub fn slerp(self, other: Quaternion, t: f32) -> Quaternion {
+        let other = self.nearest(other);
+        let cos = self.dot(other).min(1.0);
+
+        // nearly the same orientation: the arc is a straight line and its
+        // angle too small to divide by.
+        if cos > 0.9995 {
+            return self.nlerp(other, t);
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        self.scale(a).add(other.scale(b))
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Quaternion {
+        Quaternion::identity()
+    }
+}
+
+// composition: rotate by `other` first, then by `self`, like matrices.
+impl Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Quaternion) -> Quaternion {
+        let (a, b) = (self, other);
+        Quaternion {
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        }
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Quaternion {
+        self.scale(-1.0)
+    }
+}
+
+impl From<Quaternion> for Matrix4x4 {
+    fn from(q: Quaternion) -> Matrix4x4 {
+        q.to_matrix()
+    }
+}
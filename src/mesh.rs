@@ -0,0 +1,459 @@
+use error::Error;
+use geometry::{Point, Point3D, Triangle};
+use matrix::Matrix4x4;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::path::Path;
+use tobj;
+
+/*
+ * Definition of a Mesh and implementation.
+ *
+ * An indexed triangle mesh. Texture coordinates are optional; when present
+ * there is exactly one per position, so a single index addresses all
+ * attributes of a vertex. Normals missing from the source are computed by
+ * `compute_normals`; tangents and bitangents for normal mapping are derived
+ * from the texture coordinates by `compute_tangents`.
+ */
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub name: String,
+    pub positions: Vec<Point3D>,
+    pub normals: Vec<Point3D>,
+    pub texcoords: Vec<Point>,
+    // per-vertex tangent frame; empty without texture coordinates.
+    pub tangents: Vec<Point3D>,
+    pub bitangents: Vec<Point3D>,
+    pub indices: Vec<usize>,
+    // index into the materials returned alongside the mesh by `load_obj`.
+    pub material_id: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum MeshError {
+    // the flat attribute arrays are not a whole number of vectors.
+    MalformedAttribute(&'static str),
+    // an attribute is present but not given for every position.
+    AttributeCount {
+        attribute: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    // an attribute needed for rendering is absent, e.g. texcoords for texturing.
+    MissingAttribute(&'static str),
+    IncompleteFace,
+    IndexOutOfRange {
+        face: usize,
+        index: usize,
+    },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MeshError::MalformedAttribute(attribute) => {
+                write!(f, "{} do not divide into whole vectors", attribute)
+            }
+            MeshError::AttributeCount {
+                attribute,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} {} (one per position), found {}",
+                expected, attribute, found
+            ),
+            MeshError::MissingAttribute(attribute) => write!(f, "mesh has no {}", attribute),
+            MeshError::IncompleteFace => write!(f, "index count is not a multiple of 3"),
+            MeshError::IndexOutOfRange { face, index } => {
+                write!(f, "face {} references missing vertex {}", face, index)
+            }
+        }
+    }
+}
+
+impl error::Error for MeshError {}
+
+/**
+ * A face of a mesh: its triangle in model space plus the attributes of
+ * its three vertices.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct Face {
+    pub index: usize,
+    pub indices: [usize; 3],
+    pub triangle: Triangle,
+    pub normals: Option<[Point3D; 3]>,
+    pub texcoords: Option<[Point; 3]>,
+    pub tangents: Option<[Point3D; 3]>,
+    pub bitangents: Option<[Point3D; 3]>,
+}
+
+impl Face {
+    /**
+     * Unit normal of the face, oriented by the counter-clockwise winding of
+     * its vertices.
+     */
+    pub fn normal(&self) -> Point3D {
+        let v = self.triangle.vertices;
+        let mut normal = (v[1] - v[0]) * (v[2] - v[0]);
+        normal.normalize();
+        normal
+    }
+}
+
+impl Mesh {
+    pub fn new(
+        positions: Vec<Point3D>,
+        normals: Vec<Point3D>,
+        texcoords: Vec<Point>,
+        indices: Vec<usize>,
+    ) -> Result<Mesh, Error> {
+        let mut mesh = Mesh {
+            name: String::new(),
+            positions,
+            normals,
+            texcoords,
+            tangents: vec![],
+            bitangents: vec![],
+            indices,
+            material_id: None,
+        };
+        mesh.validate()?;
+        mesh.complete_attributes();
+
+        Ok(mesh)
+    }
+
+    /**
+     * Load every model of an OBJ file, together with the materials of its
+     * MTL library.
+     */
+    pub fn load_obj(path: &Path) -> Result<(Vec<Mesh>, Vec<tobj::Material>), Error> {
+        let (models, materials) = tobj::load_obj(path)?;
+
+        let meshes = models
+            .into_iter()
+            .map(|model| Mesh::from_tobj(model.name, model.mesh))
+            .collect::<Result<Vec<Mesh>, Error>>()?;
+
+        Ok((meshes, materials))
+    }
+
+    pub fn from_tobj(name: String, mesh: tobj::Mesh) -> Result<Mesh, Error> {
+        if !mesh.positions.len().is_multiple_of(3) {
+            return Err(Error::Mesh(MeshError::MalformedAttribute("positions")));
+        }
+        if !mesh.normals.len().is_multiple_of(3) {
+            return Err(Error::Mesh(MeshError::MalformedAttribute("normals")));
+        }
+        if !mesh.texcoords.len().is_multiple_of(2) {
+            return Err(Error::Mesh(MeshError::MalformedAttribute("texcoords")));
+        }
+
+        let mut result = Mesh {
+            name,
+            positions: mesh
+                .positions
+                .chunks(3)
+                .map(|p| Point3D::new([p[0], p[1], p[2]]))
+                .collect(),
+            normals: mesh
+                .normals
+                .chunks(3)
+                .map(|n| Point3D::new([n[0], n[1], n[2]]))
+                .collect(),
+            texcoords: mesh
+                .texcoords
+                .chunks(2)
+                .map(|t| Point { x: t[0], y: t[1] })
+                .collect(),
+            tangents: vec![],
+            bitangents: vec![],
+            indices: mesh.indices.iter().map(|&idx| idx as usize).collect(),
+            material_id: mesh.material_id,
+        };
+        result.validate()?;
+        result.complete_attributes();
+
+        Ok(result)
+    }
+
+    // fill in the normals (when the source had none) and the tangent frame.
+    fn complete_attributes(&mut self) {
+        if self.normals.is_empty() {
+            self.compute_normals();
+        }
+        if !self.texcoords.is_empty() {
+            self.compute_tangents();
+        }
+    }
+
+    fn validate(&self) -> Result<(), MeshError> {
+        let expected = self.positions.len();
+
+        if !self.normals.is_empty() && self.normals.len() != expected {
+            return Err(MeshError::AttributeCount {
+                attribute: "normals",
+                expected,
+                found: self.normals.len(),
+            });
+        }
+        if !self.texcoords.is_empty() && self.texcoords.len() != expected {
+            return Err(MeshError::AttributeCount {
+                attribute: "texcoords",
+                expected,
+                found: self.texcoords.len(),
+            });
+        }
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshError::IncompleteFace);
+        }
+        if let Some(pos) = self.indices.iter().position(|&idx| idx >= expected) {
+            return Err(MeshError::IndexOutOfRange {
+                face: pos / 3,
+                index: self.indices[pos],
+            });
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Replace the vertex normals by the average of the normals of the faces
+     * around each vertex, weighted by face area. The cross product of two
+     * edges has length twice the area of the face, so summing the raw cross
+     * products does the weighting.
+     */
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Point3D::zero(); self.positions.len()];
+
+        for face in self.faces() {
+            let v = face.triangle.vertices;
+            let xprod = (v[1] - v[0]) * (v[2] - v[0]);
+
+            for &idx in face.indices.iter() {
+                normals[idx] = normals[idx] + xprod;
+            }
+        }
+
+        // vertices only touched by degenerate faces keep a zero normal.
+        for normal in normals.iter_mut() {
+            if normal.length() > 0.0 {
+                normal.normalize();
+            }
+        }
+
+        self.normals = normals;
+    }
+
+    /**
+     * Per-vertex tangents and bitangents: the model space directions in
+     * which the u and v texture coordinates increase. Face contributions
+     * are accumulated per vertex, then the tangent is made orthogonal to the
+     * vertex normal and the bitangent is rebuilt as n x t, flipped when the
+     * texture is mirrored.
+     */
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Point3D::zero(); self.positions.len()];
+        let mut bitangents = vec![Point3D::zero(); self.positions.len()];
+
+        for face in self.faces() {
+            let uv = match face.texcoords {
+                Some(uv) => uv,
+                None => return,
+            };
+            let v = face.triangle.vertices;
+
+            let (e1, e2) = (v[1] - v[0], v[2] - v[0]);
+            let (du1, dv1) = (uv[1].x - uv[0].x, uv[1].y - uv[0].y);
+            let (du2, dv2) = (uv[2].x - uv[0].x, uv[2].y - uv[0].y);
+
+            let det = du1 * dv2 - du2 * dv1;
+            if det == 0.0 {
+                continue;
+            }
+
+            let tangent = (e1 * dv2 - e2 * dv1) / det;
+            let bitangent = (e2 * du1 - e1 * du2) / det;
+
+            for &idx in face.indices.iter() {
+                tangents[idx] = tangents[idx] + tangent;
+                bitangents[idx] = bitangents[idx] + bitangent;
+            }
+        }
+
+        for idx in 0..self.positions.len() {
+            let n = self.normals[idx];
+
+            // Gram-Schmidt
+            let mut t = tangents[idx] - n * n.dot(tangents[idx]);
+            if t.length() == 0.0 {
+                continue;
+            }
+            t.normalize();
+
+            let b = n * t;
+            let handedness = if b.dot(bitangents[idx]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+
+            tangents[idx] = t;
+            bitangents[idx] = b * handedness;
+        }
+
+        self.tangents = tangents;
+        self.bitangents = bitangents;
+    }
+
+    /**
+     * Move the mesh by `transform`: positions as points, normals by the
+     * inverse transpose so they stay perpendicular to the surface under
+     * non-uniform scaling. A transform that mirrors the mesh also reverses
+     * the winding of its faces, keeping them counter-clockwise from the
+     * outside. Fails for a singular transform, which flattens the mesh.
+     */
+    pub fn transform(&mut self, transform: &Matrix4x4) -> Result<(), Error> {
+        let normal_matrix = transform.inverse_transpose()?;
+
+        for p in self.positions.iter_mut() {
+            let v = transform * [p.x, p.y, p.z, 1.0];
+            *p = Point3D::new([v[0] / v[3], v[1] / v[3], v[2] / v[3]]);
+        }
+        for n in self.normals.iter_mut() {
+            let v = normal_matrix * [n.x, n.y, n.z, 0.0];
+            *n = Point3D::new([v[0], v[1], v[2]]);
+            if n.length() > 0.0 {
+                n.normalize();
+            }
+        }
+        if transform.determinant() < 0.0 {
+            for face in self.indices.chunks_mut(3) {
+                face.swap(1, 2);
+            }
+        }
+        if !self.texcoords.is_empty() {
+            self.compute_tangents();
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Fail unless the mesh has texture coordinates, for callers about to
+     * texture it.
+     */
+    pub fn require_texcoords(&self) -> Result<(), Error> {
+        if self.texcoords.is_empty() {
+            return Err(Error::Mesh(MeshError::MissingAttribute("texcoords")));
+        }
+        Ok(())
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn face(&self, index: usize) -> Face {
+        let indices = [
+            self.indices[3 * index],
+            self.indices[3 * index + 1],
+            self.indices[3 * index + 2],
+        ];
+
+        Face {
+            index,
+            indices,
+            triangle: Triangle {
+                vertices: [
+                    self.positions[indices[0]],
+                    self.positions[indices[1]],
+                    self.positions[indices[2]],
+                ],
+            },
+            normals: if self.normals.is_empty() {
+                None
+            } else {
+                Some([
+                    self.normals[indices[0]],
+                    self.normals[indices[1]],
+                    self.normals[indices[2]],
+                ])
+            },
+            texcoords: if self.texcoords.is_empty() {
+                None
+            } else {
+                Some([
+                    self.texcoords[indices[0]],
+                    self.texcoords[indices[1]],
+                    self.texcoords[indices[2]],
+                ])
+            },
+            tangents: if self.tangents.is_empty() {
+                None
+            } else {
+                Some([
+                    self.tangents[indices[0]],
+                    self.tangents[indices[1]],
+                    self.tangents[indices[2]],
+                ])
+            },
+            bitangents: if self.bitangents.is_empty() {
+                None
+            } else {
+                Some([
+                    self.bitangents[indices[0]],
+                    self.bitangents[indices[1]],
+                    self.bitangents[indices[2]],
+                ])
+            },
+        }
+    }
+
+    pub fn faces<'a>(&'a self) -> impl Iterator<Item = Face> + 'a {
+        (0..self.num_faces()).map(move |index| self.face(index))
+    }
+
+    /**
+     * Every edge of the mesh once, as a pair of position indices with the
+     * smaller first, in ascending order. The OBJ loader duplicates vertices
+     * along UV and normal seams, so vertices are matched by position: an
+     * edge is named by the first index of each of its end points.
+     */
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = self
+            .face_edges()
+            .into_iter()
+            .map(|(edge, _)| edge)
+            .collect();
+        edges.dedup();
+        edges
+    }
+
+    /**
+     * The three edges of every face, named as by `edges`, each with the
+     * index of the face, sorted so the faces sharing an edge are adjacent.
+     */
+    pub(crate) fn face_edges(&self) -> Vec<((usize, usize), usize)> {
+        let mut first = HashMap::with_capacity(self.positions.len());
+        let canonical: Vec<usize> = self
+            .positions
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| *first.entry(p.to_array().map(f32::to_bits)).or_insert(idx))
+            .collect();
+
+        let mut edges = Vec::with_capacity(self.indices.len());
+        for (index, face) in self.indices.chunks(3).enumerate() {
+            for j in 0..3 {
+                let (a, b) = (canonical[face[j]], canonical[face[(j + 1) % 3]]);
+                edges.push(((a.min(b), a.max(b)), index));
+            }
+        }
+        edges.sort_unstable();
+        edges
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+/*
+ * Binary ("raw") Netpbm writers: P6 for RGB images (.ppm) and P5 for
+ * grayscale ones (.pgm). Both are an ASCII header followed by the samples,
+ * one byte each, top row first.
+ */
+
+/**
+ * Write `rgb`, 3 bytes per pixel, as a binary PPM.
+ */
+pub fn write_ppm<W: Write>(
+    writer: &mut W,
+    width: usize,
+    height: usize,
+    rgb: &[u8],
+) -> io::Result<()> {
+    check_len(rgb.len(), 3 * width * height)?;
+
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+    writer.write_all(rgb)
+}
+
+/**
+ * Write `gray`, 1 byte per pixel, as a binary PGM.
+ */
+pub fn write_pgm<W: Write>(
+    writer: &mut W,
+    width: usize,
+    height: usize,
+    gray: &[u8],
+) -> io::Result<()> {
+    check_len(gray.len(), width * height)?;
+
+    write!(writer, "P5\n{} {}\n255\n", width, height)?;
+    writer.write_all(gray)
+}
+
+// the samples must fill the image exactly, or the file would not parse.
+fn check_len(found: usize, expected: usize) -> io::Result<()> {
+    if found != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected {} sample bytes, found {}", expected, found),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,498 @@
+use geometry::{Point3D, Triangle};
+use shader::{Derivatives, Shader, Varying};
+use {Interpolation, RGBPixel, Rasterizer};
+
+/*
+ * Scan conversion of screen space triangles into a band of image rows.
+ *
+ * Pixel (x, y) covers the unit square with its lower left corner at (x, y),
+ * counting y from the bottom of the image. Coverage and depth are tested at
+ * one or more sample positions inside the pixel: just its center without
+ * multisampling. Rows are stored top row first, so pixel row y lives in
+ * buffer row `height - 1 - y`, and a pixel's samples are stored next to
+ * each other.
+ *
+ * `ImageMeta::draw_mesh` either hands the whole image over as one band, or
+ * splits it into bands of `BAND_ROWS` rows that worker threads fill in
+ * parallel. A pixel is only ever written by the band that owns its row, and
+ * every band draws its triangles in submission order, so both paths produce
+ * the same image.
+ */
+
+pub const BAND_ROWS: usize = 32;
+
+const MAX_SAMPLES: usize = 8;
+
+// sample positions in sixteenths of a pixel from its lower left corner: the
+// standard Direct3D patterns for 1, 2, 4 and 8 samples.
+const PATTERN_1: [(i64, i64); 1] = [(8, 8)];
+const PATTERN_2: [(i64, i64); 2] = [(12, 12), (4, 4)];
+const PATTERN_4: [(i64, i64); 4] = [(6, 2), (14, 6), (2, 10), (10, 14)];
+const PATTERN_8: [(i64, i64); 8] = [
+    (9, 5),
+    (7, 11),
+    (13, 9),
+    (5, 3),
+    (3, 13),
+    (1, 7),
+    (11, 15),
+    (15, 1),
+];
+
+fn sample_pattern(samples: usize) -> &'static [(i64, i64)] {
+    match samples {
+        1 => &PATTERN_1,
+        2 => &PATTERN_2,
+        4 => &PATTERN_4,
+        8 => &PATTERN_8,
+        _ => panic!("no sample pattern for {} samples", samples),
+    }
+}
+
+/**
+ * A triangle after the viewport transform, ready to be rasterized.
+ */
+pub struct Primitive<V> {
+    pub triangle: Triangle,
+    // 1/w of each vertex, for perspective-correct interpolation.
+    pub inv_w: Point3D,
+    pub varyings: [V; 3],
+}
+
+/**
+ * The rows `first_row..first_row + rows` of an image `width` x `height`;
+ * `buffer` and `zbuffer` hold just these rows, with `samples` entries per
+ * pixel.
+ */
+pub struct Band<'a> {
+    pub buffer: &'a mut [RGBPixel],
+    pub zbuffer: &'a mut [f32],
+    pub width: usize,
+    pub height: usize,
+    pub first_row: usize,
+    pub rows: usize,
+    pub samples: usize,
+}
+
+/**
+ * Row of the image a sample at height `y` lands in. The origin is in the
+ * lower left-hand corner and rows are stored top row first.
+ */
+pub fn row_of(y: f32, height: usize) -> usize {
+    clamp(height as f32 - y, height)
+}
+
+pub fn col_of(x: f32, width: usize) -> usize {
+    clamp(x, width)
+}
+
+// shift a float coordinate into a discrete one in [0, n).
+fn clamp(num: f32, n: usize) -> usize {
+    if num as i32 >= n as i32 {
+        n - 1
+    } else if num <= 0.0 {
+        0
+    } else {
+        num.floor() as usize
+    }
+}
+
+/**
+ * Rows of the image whose pixel centers may fall in the triangle, as an
+ * inclusive range of buffer rows, or `None` when it is off screen.
+ */
+pub fn row_range(triangle: &Triangle, width: usize, height: usize) -> Option<(usize, usize)> {
+    pixel_bounds(triangle, width, height, 0, height - 1)
+        .map(|(_, _, y_min, y_max)| (height - 1 - y_max, height - 1 - y_min))
+}
+
+/**
+ * Candidate pixels of a triangle: the on-screen part of its bounding box,
+ * limited to the buffer rows `first_row..=last_row`. Returns the inclusive
+ * ranges of x and of y (from the bottom), or `None` when empty.
+ */
+fn pixel_bounds(
+    triangle: &Triangle,
+    width: usize,
+    height: usize,
+    first_row: usize,
+    last_row: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let (sw, ne) = triangle.compute_bbox();
+
+    // the box has integer corners, so a pixel with any of its samples in
+    // the box starts at or after its lower left corner and ends at or
+    // before its upper right one.
+    let x_min = sw.x.max(0.0);
+    let x_max = (ne.x - 1.0).min((width - 1) as f32);
+    let y_min = sw.y.max(0.0).max((height - 1 - last_row) as f32);
+    let y_max = (ne.y - 1.0).min((height - 1 - first_row) as f32);
+
+    if x_min > x_max || y_min > y_max {
+        return None;
+    }
+    Some((
+        x_min as usize,
+        x_max as usize,
+        y_min as usize,
+        y_max as usize,
+    ))
+}
+
+pub fn rasterize<S: Shader>(
+    band: &mut Band,
+    interpolation: Interpolation,
+    rasterizer: Rasterizer,
+    primitive: &Primitive<S::Varying>,
+    shader: &S,
+) {
+    match rasterizer {
+        // a constant sample count lets the per-pixel loops unroll.
+        Rasterizer::EdgeFunction => match band.samples {
+            1 => scan_edges::<S, 1>(band, interpolation, primitive, shader),
+            2 => scan_edges::<S, 2>(band, interpolation, primitive, shader),
+            4 => scan_edges::<S, 4>(band, interpolation, primitive, shader),
+            8 => scan_edges::<S, 8>(band, interpolation, primitive, shader),
+            n => panic!("no sample pattern for {} samples", n),
+        },
+        Rasterizer::BoundingBox => scan_bbox(band, interpolation, primitive, shader),
+    }
+}
+
+fn band_bounds(band: &Band, triangle: &Triangle) -> Option<(usize, usize, usize, usize)> {
+    let last_row = band.first_row + band.rows - 1;
+    pixel_bounds(triangle, band.width, band.height, band.first_row, last_row)
+}
+
+// index into the band's buffers of the first sample of the pixel at (x, y).
+fn band_index(band: &Band, x: usize, y: usize) -> usize {
+    ((band.height - 1 - y - band.first_row) * band.width + x) * band.samples
+}
+
+// interpolation weights for barycentric coordinates in screen space.
+fn weights<V>(primitive: &Primitive<V>, interpolation: Interpolation, bc: Point3D) -> Point3D {
+    match interpolation {
+        Interpolation::Perspective => {
+            let inv_w = primitive.inv_w;
+            let bc_w = Point3D {
+                x: bc.x * inv_w.x,
+                y: bc.y * inv_w.y,
+                z: bc.z * inv_w.z,
+            };
+            bc_w / (bc_w.x + bc_w.y + bc_w.z)
+        }
+        Interpolation::Affine => bc,
+    }
+}
+
+// barycentric coordinates of any point, inside the triangle or not.
+fn barycentric_at(triangle: &Triangle, x: f32, y: f32) -> Point3D {
+    let v = &triangle.vertices;
+    let edge = |p: Point3D, q: Point3D| (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x);
+    let bc = Point3D {
+        x: edge(v[1], v[2]),
+        y: edge(v[2], v[0]),
+        z: edge(v[0], v[1]),
+    };
+    bc / (bc.x + bc.y + bc.z)
+}
+
+/**
+ * Derivatives of the varyings across the 2x2 quad holding pixel (x, y),
+ * from the weights at the centers of its pixels.
+ */
+fn quad_derivatives<V: Varying>(
+    primitive: &Primitive<V>,
+    interpolation: Interpolation,
+    x: usize,
+    y: usize,
+) -> Derivatives<V> {
+    let (qx, qy) = ((x & !1) as f32 + 0.5, (y & !1) as f32 + 0.5);
+    let at = |x: f32, y: f32| {
+        let bc = barycentric_at(&primitive.triangle, x, y);
+        weights(primitive, interpolation, bc)
+    };
+    let origin = at(qx, qy);
+
+    Derivatives {
+        dx: V::interpolate(&primitive.varyings, at(qx + 1.0, qy) - origin),
+        dy: V::interpolate(&primitive.varyings, at(qx, qy + 1.0) - origin),
+    }
+}
+
+/**
+ * Depth test the samples set in `coverage` of pixel (x, y), whose first
+ * sample is at `idx`; `bc_at` gives the barycentric coordinates at a
+ * sample. When any of them passes, the fragment shader runs once, at the
+ * centroid of those samples, and its color is stored in each of them.
+ */
+fn shade<S: Shader, F: Fn(usize) -> Point3D>(
+    band: &mut Band,
+    (x, y, idx): (usize, usize, usize),
+    interpolation: Interpolation,
+    primitive: &Primitive<S::Varying>,
+    shader: &S,
+    coverage: u32,
+    bc_at: F,
+) {
+    let v = &primitive.triangle.vertices;
+    let mut passed = 0_u32;
+    let mut depths = [0.0; MAX_SAMPLES];
+    let mut centroid = Point3D::zero();
+
+    for sample in (0..band.samples).filter(|s| coverage & (1 << s) != 0) {
+        let bc = bc_at(sample);
+        let z = bc.x * v[0].z + bc.y * v[1].z + bc.z * v[2].z;
+
+        if band.zbuffer[idx + sample] < z {
+            depths[sample] = z;
+            passed |= 1 << sample;
+            centroid = centroid + bc;
+        }
+    }
+
+    if passed != 0 {
+        let bc = centroid / passed.count_ones() as f32;
+        let weights = weights(primitive, interpolation, bc);
+        let varying = S::Varying::interpolate(&primitive.varyings, weights);
+
+        let color = if S::USES_DERIVATIVES {
+            let derivatives = quad_derivatives(primitive, interpolation, x, y);
+            shader.fragment_with_derivatives(varying, derivatives)
+        } else {
+            shader.fragment(varying)
+        };
+        if let Some(color) = color {
+            for sample in (0..band.samples).filter(|s| passed & (1 << s) != 0) {
+                band.zbuffer[idx + sample] = depths[sample];
+                band.buffer[idx + sample] = color;
+            }
+        }
+    }
+}
+
+/**
+ * Reference rasterizer: barycentric coordinates from scratch at every
+ * sample of the bounding box. Samples on a shared edge are drawn by both
+ * triangles.
+ */
+fn scan_bbox<S: Shader>(
+    band: &mut Band,
+    interpolation: Interpolation,
+    primitive: &Primitive<S::Varying>,
+    shader: &S,
+) {
+    let triangle = &primitive.triangle;
+    let (x_min, x_max, y_min, y_max) = match band_bounds(band, triangle) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+
+    let pattern = sample_pattern(band.samples);
+
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let mut bcs = [Point3D::zero(); MAX_SAMPLES];
+            let mut coverage = 0;
+
+            for (sample, &(sx, sy)) in pattern.iter().enumerate() {
+                let bc = triangle.barycentric_coords_f32(
+                    x as f32 + sx as f32 / 16.0,
+                    y as f32 + sy as f32 / 16.0,
+                );
+                if (bc.x >= 0.0) && (bc.y >= 0.0) && (bc.z >= 0.0) {
+                    bcs[sample] = bc;
+                    coverage |= 1 << sample;
+                }
+            }
+
+            if coverage != 0 {
+                let idx = band_index(band, x, y);
+                let bc_at = |sample: usize| bcs[sample];
+                let pixel = (x, y, idx);
+                shade(
+                    band,
+                    pixel,
+                    interpolation,
+                    primitive,
+                    shader,
+                    coverage,
+                    bc_at,
+                );
+            }
+        }
+    }
+}
+
+/*
+ * Vertices are snapped to a grid of 1/2^SUBPIXEL_BITS of a pixel, which
+ * makes the edge functions integers. They are then exact at every sample
+ * and stepping them from one sample to the next loses nothing, however far
+ * the walk started: a band starting halfway down a triangle sees the same
+ * values as one that walked there.
+ */
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+
+/**
+ * E(x, y) = a * x + b * y + c, in subpixel units. Positive on the left of
+ * the directed edge from p to q, zero on the edge.
+ */
+#[derive(Copy, Clone)]
+struct Edge {
+    a: i64,
+    b: i64,
+    c: i64,
+}
+
+impl Edge {
+    fn new(p: (i64, i64), q: (i64, i64)) -> Edge {
+        let (dx, dy) = (q.0 - p.0, q.1 - p.1);
+        Edge {
+            a: -dy,
+            b: dx,
+            c: dy * p.0 - dx * p.1,
+        }
+    }
+
+    fn at(&self, x: i64, y: i64) -> i64 {
+        self.a * x + self.b * y + self.c
+    }
+
+    /**
+     * With the triangle counter-clockwise (y up), a top edge runs exactly
+     * right to left and a left edge runs downwards.
+     */
+    fn is_top_left(&self) -> bool {
+        self.a > 0 || (self.a == 0 && self.b < 0)
+    }
+
+    fn flip(&self) -> Edge {
+        Edge {
+            a: -self.a,
+            b: -self.b,
+            c: -self.c,
+        }
+    }
+}
+
+fn snap(p: Point3D) -> (i64, i64) {
+    let scale = SUBPIXEL as f32;
+    ((p.x * scale).round() as i64, (p.y * scale).round() as i64)
+}
+
+/**
+ * Incremental rasterizer: the edge functions are set up once per triangle
+ * and stepped across each row, which avoids any per-sample setup.
+ *
+ * Samples exactly on an edge follow the top-left fill rule: they belong to
+ * the triangle only when the edge is a top or a left edge. Two triangles
+ * sharing an edge see it with opposite directions, so exactly one of them
+ * draws those pixels and a mesh is covered without gaps or overdraw.
+ */
+fn scan_edges<S: Shader, const N: usize>(
+    band: &mut Band,
+    interpolation: Interpolation,
+    primitive: &Primitive<S::Varying>,
+    shader: &S,
+) {
+    let v = &primitive.triangle.vertices;
+    let p = [snap(v[0]), snap(v[1]), snap(v[2])];
+
+    // the edge opposite each vertex; its function is that vertex's weight.
+    let mut edges = [
+        Edge::new(p[1], p[2]),
+        Edge::new(p[2], p[0]),
+        Edge::new(p[0], p[1]),
+    ];
+    let mut area = edges[0].at(p[0].0, p[0].1);
+
+    if area == 0 {
+        return;
+    }
+    // accept both windings.
+    if area < 0 {
+        for edge in edges.iter_mut() {
+            *edge = edge.flip();
+        }
+        area = -area;
+    }
+    let inv_area = 1.0 / area as f32;
+
+    let (x_min, x_max, y_min, y_max) = match band_bounds(band, &primitive.triangle) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+
+    // the edge functions are integers, so "> 0" is ">= 1".
+    let bias = [
+        if edges[0].is_top_left() { 0 } else { 1 },
+        if edges[1].is_top_left() { 0 } else { 1 },
+        if edges[2].is_top_left() { 0 } else { 1 },
+    ];
+    let step = [
+        edges[0].a * SUBPIXEL,
+        edges[1].a * SUBPIXEL,
+        edges[2].a * SUBPIXEL,
+    ];
+
+    // how far each sample moves the edge functions from the pixel's lower
+    // left corner.
+    let pattern = sample_pattern(N);
+    let mut offsets = [[0; 3]; N];
+    for (offset, &(sx, sy)) in offsets.iter_mut().zip(pattern.iter()) {
+        let (sx, sy) = (sx * SUBPIXEL / 16, sy * SUBPIXEL / 16);
+        for (o, edge) in offset.iter_mut().zip(edges.iter()) {
+            *o = edge.a * sx + edge.b * sy;
+        }
+    }
+
+    for y in y_min..=y_max {
+        let cx = x_min as i64 * SUBPIXEL;
+        let cy = y as i64 * SUBPIXEL;
+        let mut w = [
+            edges[0].at(cx, cy),
+            edges[1].at(cx, cy),
+            edges[2].at(cx, cy),
+        ];
+        let row = band_index(band, 0, y);
+
+        for x in x_min..=x_max {
+            let mut coverage = 0;
+
+            for (sample, offset) in offsets.iter().enumerate() {
+                if w[0] + offset[0] >= bias[0]
+                    && w[1] + offset[1] >= bias[1]
+                    && w[2] + offset[2] >= bias[2]
+                {
+                    coverage |= 1 << sample;
+                }
+            }
+
+            if coverage != 0 {
+                let idx = row + x * N;
+                let bc_at = |sample: usize| {
+                    let offset = offsets[sample];
+                    Point3D {
+                        x: (w[0] + offset[0]) as f32 * inv_area,
+                        y: (w[1] + offset[1]) as f32 * inv_area,
+                        z: (w[2] + offset[2]) as f32 * inv_area,
+                    }
+                };
+                let pixel = (x, y, idx);
+                shade(
+                    band,
+                    pixel,
+                    interpolation,
+                    primitive,
+                    shader,
+                    coverage,
+                    bc_at,
+                );
+            }
+            w[0] += step[0];
+            w[1] += step[1];
+            w[2] += step[2];
+        }
+    }
+}
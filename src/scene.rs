@@ -0,0 +1,297 @@
+use camera::Camera;
+use error::Error;
+use geometry::Point3D;
+use matrix::Matrix4x4;
+use mesh::Mesh;
+use shading::{self, NormalMap, PhongShader, Specular};
+use shadow::ShadowMap;
+use std::path::Path;
+use std::sync::Arc;
+use texture::Texture;
+use {ImageMeta, RGBPixel};
+
+/*
+ * A scene graph: a tree of nodes, each with a transform relative to its
+ * parent. The world transform of a node is the product of the transforms
+ * on the path from the root down to it, so moving a node moves everything
+ * below it. Nodes refer to meshes by index into `Scene::meshes`, letting
+ * one mesh be drawn in several places.
+ *
+ * Rendering walks the tree and draws each mesh where its node puts it with
+ * a `PhongShader` lit by a single directional light. The shader applies the
+ * node's world transform, so meshes are never copied to be drawn.
+ */
+
+/**
+ * How a mesh is shaded. It is the `PhongShader` setup for one node: the
+ * base color, or a texture, and the lighting coefficients.
+ */
+pub struct Material {
+    pub color: RGBPixel,
+    pub lighting: shading::Material,
+    pub specular: Specular,
+    // shared with the shader and any other material using the same images.
+    pub texture: Option<Arc<Texture>>,
+    pub normal_map: Option<Arc<NormalMap>>,
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material {
+            color: RGBPixel {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+            lighting: shading::Material::default(),
+            specular: Specular::BlinnPhong,
+            texture: None,
+            normal_map: None,
+        }
+    }
+}
+
+pub struct Node {
+    pub name: String,
+    // relative to the parent node.
+    pub transform: Matrix4x4,
+    // index into `Scene::meshes`.
+    pub mesh: Option<usize>,
+    // `None` inherits the material of the parent. Nodes drawn alike can
+    // share one.
+    pub material: Option<Arc<Material>>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /**
+     * An empty node at its parent's origin.
+     */
+    pub fn new(name: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            transform: Matrix4x4::identity(),
+            mesh: None,
+            material: None,
+            children: vec![],
+        }
+    }
+
+    /**
+     * Append `child` and return it, to keep building below it.
+     */
+    pub fn add_child(&mut self, child: Node) -> &mut Node {
+        self.children.push(child);
+        self.children.last_mut().unwrap()
+    }
+
+    /**
+     * The first node named `name` in this subtree, searched depth first.
+     */
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .filter_map(|child| child.find(name))
+            .next()
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .filter_map(|child| child.find_mut(name))
+            .next()
+    }
+}
+
+/**
+ * A node reached while walking the scene, with the state inherited from
+ * its ancestors.
+ */
+pub struct Instance<'a> {
+    pub node: &'a Node,
+    // from the node's space to world space.
+    pub transform: Matrix4x4,
+    pub material: &'a Material,
+}
+
+pub struct Scene {
+    pub root: Node,
+    pub meshes: Vec<Mesh>,
+    // direction the light travels in, in world space.
+    pub light_dir: Point3D,
+    pub shadows: bool,
+    // material of nodes without one of their own or on any ancestor.
+    pub material: Material,
+}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
+
+impl Scene {
+    /**
+     * An empty scene lit from the viewer's side, along -z.
+     */
+    pub fn new() -> Scene {
+        Scene {
+            root: Node::new("root"),
+            meshes: vec![],
+            light_dir: Point3D::new([0.0, 0.0, -1.0]),
+            shadows: false,
+            material: Material::default(),
+        }
+    }
+
+    /**
+     * Store `mesh` in the scene and return the index nodes refer to it by.
+     */
+    pub fn add_mesh(&mut self, mesh: Mesh) -> usize {
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    /**
+     * Add the models of an OBJ file to the scene's meshes. The returned
+     * node, named after the file, has one child per model and still has to
+     * be attached to the tree.
+     */
+    pub fn load_obj(&mut self, path: &Path) -> Result<Node, Error> {
+        let (meshes, _) = Mesh::load_obj(path)?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut node = Node::new(&name);
+        for mesh in meshes {
+            let mut child = Node::new(&mesh.name);
+            child.mesh = Some(self.add_mesh(mesh));
+            node.add_child(child);
+        }
+
+        Ok(node)
+    }
+
+    /**
+     * Every node of the tree, parents before their children, with its world
+     * transform and the material it is drawn with.
+     */
+    pub fn instances(&self) -> Vec<Instance<'_>> {
+        let mut instances = vec![];
+        collect(
+            &self.root,
+            Matrix4x4::identity(),
+            &self.material,
+            &mut instances,
+        );
+        instances
+    }
+
+    /**
+     * The meshes of the scene in world space, each with its material. They
+     * are copies; `render` draws the scene without them.
+     */
+    pub fn world_meshes(&self) -> Result<Vec<(Mesh, &Material)>, Error> {
+        let mut meshes = vec![];
+
+        for (mesh, transform, material) in self.drawn()? {
+            let mut mesh = mesh.clone();
+            mesh.transform(&transform)?;
+            meshes.push((mesh, material));
+        }
+
+        Ok(meshes)
+    }
+
+    // every mesh to draw with its world transform and material.
+    fn drawn(&self) -> Result<Vec<(&Mesh, Matrix4x4, &Material)>, Error> {
+        let mut drawn = vec![];
+
+        for instance in self.instances() {
+            if let Some(index) = instance.node.mesh {
+                let mesh = self.meshes.get(index).ok_or_else(|| Error::UnknownMesh {
+                    node: instance.node.name.clone(),
+                    index,
+                })?;
+                drawn.push((mesh, instance.transform, instance.material));
+            }
+        }
+
+        Ok(drawn)
+    }
+
+    /**
+     * Draw the scene as seen by `camera`. With `shadows` on, a shadow map
+     * covering every mesh of the scene is rendered first.
+     */
+    pub fn render(&self, image: &mut ImageMeta, camera: &Camera) -> Result<(), Error> {
+        let drawn = self.drawn()?;
+        let instances: Vec<(&Mesh, Matrix4x4)> = drawn
+            .iter()
+            .map(|&(mesh, transform, _)| (mesh, transform))
+            .collect();
+        // fail before drawing anything on a transform the normals cannot
+        // follow.
+        for &(_, transform) in instances.iter() {
+            transform.inverse()?;
+        }
+
+        let mut light_dir = self.light_dir;
+        light_dir.normalize();
+
+        let mut shader = PhongShader::new(camera, light_dir);
+        if self.shadows {
+            shader.shadow = Some(ShadowMap::render_instances(
+                &ShadowMap::light_camera_instances(&instances, light_dir),
+                &instances,
+                1024,
+                1024,
+            )?);
+        }
+
+        for (mesh, transform, material) in drawn {
+            if material.texture.is_some() || material.normal_map.is_some() {
+                mesh.require_texcoords()?;
+            }
+
+            shader.set_model(transform)?;
+            shader.color = material.color;
+            shader.material = material.lighting;
+            shader.specular = material.specular;
+            shader.texture = material.texture.clone();
+            shader.normal_map = material.normal_map.clone();
+            image.draw_mesh(mesh, &mut shader);
+        }
+
+        Ok(())
+    }
+}
+
+// walk the subtree below `node`, whose parent has the given world transform
+// and material.
+fn collect<'a>(
+    node: &'a Node,
+    parent: Matrix4x4,
+    material: &'a Material,
+    instances: &mut Vec<Instance<'a>>,
+) {
+    let transform = parent * node.transform;
+    let material = node.material.as_deref().unwrap_or(material);
+
+    instances.push(Instance {
+        node,
+        transform,
+        material,
+    });
+    for child in node.children.iter() {
+        collect(child, transform, material, instances);
+    }
+}
@@ -0,0 +1,792 @@
+use antialias::Filter;
+use camera::{Camera, Projection};
+use error::Error;
+use geometry::Point3D;
+use matrix::Matrix4x4;
+use mesh::Mesh;
+use scene::{Material, Node, Scene};
+use shading::{NormalMap, Specular};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use texture::{Texture, TextureFilter};
+use {Format, ImageMeta, Multisample, RGBPixel};
+
+/*
+ * Scene files: a plain-text description of everything needed to render an
+ * image, so a render can be set up and tweaked without writing Rust.
+ *
+ * A file is a list of sections, each a header in brackets followed by
+ * `key value...` lines. Blank lines are ignored and `#` starts a comment.
+ * Paths are relative to the directory of the scene file and run to the end
+ * of the line.
+ *
+ *     [output]
+ *     path pedestal.png
+ *     size 400 400
+ *
+ *     [camera]
+ *     eye 2.5 2.5 4
+ *     target 0 0.9 0
+ *     fov 40
+ *
+ *     [material stone]
+ *     color 180 170 160
+ *
+ *     [node pedestal]
+ *     mesh cube.obj
+ *     material stone
+ *     scale 0.8 1 0.8
+ *     translate 0 0.5 0
+ *
+ * The keys of each section:
+ *
+ *     output    path PATH, format png|tga|ppm|pgm, size WIDTH HEIGHT,
+ *               msaa 1|2|4|8, ssaa N
+ *     camera    eye X Y Z, target X Y Z, up X Y Z, fov DEGREES,
+ *               orthographic HEIGHT, near Z, far Z
+ *     light     direction X Y Z, shadows on|off
+ *     material  color R G B, ambient K, diffuse K, specular K, shininess N,
+ *               highlights phong|blinn-phong, texture PATH, normal-map PATH,
+ *               filter nearest|bilinear|trilinear|anisotropic
+ *     node      mesh PATH, parent NAME, material NAME, translate X Y Z,
+ *               rotate X Y Z DEGREES, scale S | scale X Y Z
+ *
+ * `output`, `camera` and `light` appear at most once; there is a single
+ * directional light. The camera needs 0 < near < far, a field of view
+ * between 0 and 180 degrees, and an up direction off its line of sight.
+ * Materials and nodes are named and can only refer to ones declared above
+ * them. The transform lines of a node apply to its mesh in the order they
+ * are written, and a node without a parent hangs from the root of the scene.
+ */
+
+/**
+ * Where and how the image of a scene file is written.
+ */
+pub struct Output {
+    pub path: PathBuf,
+    pub format: Format,
+    pub width: usize,
+    pub height: usize,
+    pub multisample: Multisample,
+    // render this many times larger and filter down.
+    pub ssaa: usize,
+    // rasterizer threads; not part of the file, one per CPU by default.
+    pub threads: usize,
+}
+
+pub struct SceneFile {
+    pub scene: Scene,
+    pub camera: Camera,
+    pub output: Output,
+}
+
+impl SceneFile {
+    /**
+     * Read and parse a scene file, loading the meshes and textures it names.
+     */
+    pub fn load(path: &Path) -> Result<SceneFile, Error> {
+        let text = fs::read_to_string(path)?;
+        SceneFile::parse(&text, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /**
+     * Parse the text of a scene file whose relative paths start from `dir`.
+     * Errors carry the number of the offending line, counting from 1.
+     */
+    pub fn parse(text: &str, dir: &Path) -> Result<SceneFile, Error> {
+        let mut parser = Parser::new(dir);
+
+        for (index, line) in text.lines().enumerate() {
+            parser.line = index + 1;
+            let line = match line.find('#') {
+                Some(at) => &line[..at],
+                None => line,
+            };
+            let line = line.trim();
+            if !line.is_empty() {
+                parser.parse_line(line)?;
+            }
+        }
+
+        parser.finish()
+    }
+
+    /**
+     * Render the scene at the output size and settings.
+     */
+    pub fn render(&self) -> Result<ImageMeta, Error> {
+        let output = &self.output;
+        let mut image = ImageMeta::new(
+            output.width.saturating_mul(output.ssaa),
+            output.height.saturating_mul(output.ssaa),
+        )?;
+        image.threads = output.threads;
+        image.multisample = output.multisample;
+
+        self.scene.render(&mut image, &self.camera)?;
+
+        if output.ssaa > 1 {
+            image = image.downsample(output.ssaa, Filter::Tent)?;
+        }
+        Ok(image)
+    }
+}
+
+enum Section {
+    // before the first header.
+    None,
+    Output,
+    Camera,
+    Light,
+    // the material being built is `Parser::material`.
+    Material,
+    // index into `Parser::nodes`.
+    Node(usize),
+}
+
+/**
+ * A material whose textures are only loaded once its section is over, when
+ * the filter to load them with is known.
+ */
+struct MaterialSpec {
+    name: String,
+    material: Material,
+    // each path with the line it was given on.
+    texture: Option<(usize, PathBuf)>,
+    normal_map: Option<(usize, PathBuf)>,
+    filter: TextureFilter,
+}
+
+struct Parser<'a> {
+    dir: &'a Path,
+    // number of the line being parsed.
+    line: usize,
+    section: Section,
+    // the sections allowed only once that were already seen.
+    seen: Vec<String>,
+
+    output_path: PathBuf,
+    // the line of `output_path`, 0 for the default.
+    output_line: usize,
+    format: Option<Format>,
+    width: usize,
+    height: usize,
+    multisample: Multisample,
+    ssaa: usize,
+
+    eye: Point3D,
+    target: Point3D,
+    up: Point3D,
+    fov: f32,
+    // view height of an orthographic camera.
+    orthographic: Option<f32>,
+    near: f32,
+    far: f32,
+    // the line each camera property was last given on.
+    camera_lines: HashMap<String, usize>,
+
+    scene: Scene,
+    material: Option<MaterialSpec>,
+    materials: HashMap<String, Arc<Material>>,
+    // in file order, with the index of their parent; parents come first.
+    nodes: Vec<Node>,
+    parents: Vec<Option<usize>>,
+    names: HashMap<String, usize>,
+    // meshes already loaded, by path: the index and name of each model.
+    loaded: HashMap<PathBuf, Vec<(usize, String)>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(dir: &'a Path) -> Parser<'a> {
+        Parser {
+            dir,
+            line: 0,
+            section: Section::None,
+            seen: vec![],
+            output_path: dir.join("out.tga"),
+            output_line: 0,
+            format: None,
+            width: 800,
+            height: 800,
+            multisample: Multisample::Off,
+            ssaa: 1,
+            eye: Point3D::new([0.0, 0.0, 3.0]),
+            target: Point3D::zero(),
+            up: Point3D::new([0.0, 1.0, 0.0]),
+            fov: 45.0,
+            orthographic: None,
+            near: 0.1,
+            far: 100.0,
+            camera_lines: HashMap::new(),
+            scene: Scene::new(),
+            material: None,
+            materials: HashMap::new(),
+            nodes: vec![],
+            parents: vec![],
+            names: HashMap::new(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> Error {
+        Error::Parse {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), Error> {
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                return Err(self.error("section header is missing its ']'"));
+            }
+            return self.begin_section(line[1..line.len() - 1].trim());
+        }
+
+        let (key, rest) = match line.find(char::is_whitespace) {
+            Some(at) => (&line[..at], line[at..].trim_start()),
+            None => (line, ""),
+        };
+        let args = rest.split_whitespace().collect::<Vec<&str>>();
+
+        match self.section {
+            Section::None => Err(self.error(format!("'{}' is outside of any section", key))),
+            Section::Output => self.output_property(key, rest, &args),
+            Section::Camera => self.camera_property(key, &args),
+            Section::Light => self.light_property(key, &args),
+            Section::Material => self.material_property(key, rest, &args),
+            Section::Node(index) => self.node_property(index, key, rest, &args),
+        }
+    }
+
+    fn begin_section(&mut self, header: &str) -> Result<(), Error> {
+        self.end_section()?;
+
+        let (kind, name) = match header.find(char::is_whitespace) {
+            Some(at) => (&header[..at], header[at..].trim_start()),
+            None => (header, ""),
+        };
+
+        self.section = match kind {
+            "output" | "camera" | "light" => {
+                if !name.is_empty() {
+                    return Err(self.error(format!("[{}] takes no name", kind)));
+                }
+                if self.seen.iter().any(|seen| seen == kind) {
+                    return Err(self.error(format!("duplicate [{}] section", kind)));
+                }
+                self.seen.push(kind.to_string());
+
+                match kind {
+                    "output" => Section::Output,
+                    "camera" => Section::Camera,
+                    _ => Section::Light,
+                }
+            }
+            "material" => {
+                if name.is_empty() {
+                    return Err(self.error("[material] needs a name"));
+                }
+                if self.materials.contains_key(name) {
+                    return Err(self.error(format!("duplicate material '{}'", name)));
+                }
+                self.material = Some(MaterialSpec {
+                    name: name.to_string(),
+                    material: Material::default(),
+                    texture: None,
+                    normal_map: None,
+                    filter: TextureFilter::Trilinear,
+                });
+                Section::Material
+            }
+            "node" => {
+                if name.is_empty() {
+                    return Err(self.error("[node] needs a name"));
+                }
+                if self.names.contains_key(name) {
+                    return Err(self.error(format!("duplicate node '{}'", name)));
+                }
+                self.names.insert(name.to_string(), self.nodes.len());
+                self.nodes.push(Node::new(name));
+                self.parents.push(None);
+                Section::Node(self.nodes.len() - 1)
+            }
+            _ => return Err(self.error(format!("unknown section [{}]", kind))),
+        };
+
+        Ok(())
+    }
+
+    // check the camera, or complete the material being built, if any.
+    fn end_section(&mut self) -> Result<(), Error> {
+        if let Section::Camera = self.section {
+            self.check_camera()?;
+        }
+        let spec = match self.material.take() {
+            Some(spec) => spec,
+            None => return Ok(()),
+        };
+        let mut material = spec.material;
+
+        if let Some((line, ref path)) = spec.texture {
+            material.texture = Some(Arc::new(load_filtered(line, path, spec.filter)?));
+        }
+        if let Some((line, ref path)) = spec.normal_map {
+            let texture = load_filtered(line, path, spec.filter)?;
+            material.normal_map = Some(Arc::new(NormalMap::TangentSpace(texture)));
+        }
+
+        self.materials.insert(spec.name, Arc::new(material));
+        Ok(())
+    }
+
+    fn output_property(&mut self, key: &str, rest: &str, args: &[&str]) -> Result<(), Error> {
+        match key {
+            "path" => {
+                self.output_path = self.path(key, rest)?;
+                self.output_line = self.line;
+            }
+            "format" => {
+                let value = self.value(key, args)?;
+                match Format::from_extension(value) {
+                    Some(format) => self.format = Some(format),
+                    None => return Err(self.error(format!("unknown image format '{}'", value))),
+                }
+            }
+            "size" => {
+                if args.len() != 2 {
+                    return Err(self.error(format!(
+                        "size: expected WIDTH HEIGHT, found {} values",
+                        args.len()
+                    )));
+                }
+                self.width = self.number(key, args[0])?;
+                self.height = self.number(key, args[1])?;
+                if self.width == 0 || self.height == 0 {
+                    return Err(self.error("size: width and height must be positive"));
+                }
+            }
+            "msaa" => {
+                self.multisample = match self.value(key, args)? {
+                    "1" => Multisample::Off,
+                    "2" => Multisample::X2,
+                    "4" => Multisample::X4,
+                    "8" => Multisample::X8,
+                    value => {
+                        return Err(
+                            self.error(format!("msaa: expected 1, 2, 4 or 8, found '{}'", value))
+                        )
+                    }
+                }
+            }
+            "ssaa" => {
+                self.ssaa = self.number(key, self.value(key, args)?)?;
+                if self.ssaa == 0 {
+                    return Err(self.error("ssaa: the factor must be at least 1"));
+                }
+            }
+            _ => return Err(self.unknown("output", key)),
+        }
+        Ok(())
+    }
+
+    fn camera_property(&mut self, key: &str, args: &[&str]) -> Result<(), Error> {
+        match key {
+            "eye" => self.eye = self.vector(key, args)?,
+            "target" => self.target = self.vector(key, args)?,
+            "up" => self.up = self.vector(key, args)?,
+            "fov" => {
+                self.fov = self.number(key, self.value(key, args)?)?;
+                self.orthographic = None;
+            }
+            "orthographic" => {
+                self.orthographic = Some(self.number(key, self.value(key, args)?)?);
+            }
+            "near" => self.near = self.number(key, self.value(key, args)?)?,
+            "far" => self.far = self.number(key, self.value(key, args)?)?,
+            _ => return Err(self.unknown("camera", key)),
+        }
+        self.camera_lines.insert(key.to_string(), self.line);
+        Ok(())
+    }
+
+    // a camera the projection or view matrix cannot be built for, reported
+    // on the last line of the properties at fault.
+    fn check_camera(&self) -> Result<(), Error> {
+        let error = |keys: &[&str], message: String| Error::Parse {
+            line: keys
+                .iter()
+                .filter_map(|&key| self.camera_lines.get(key).cloned())
+                .max()
+                .unwrap_or(self.line),
+            message,
+        };
+
+        if !(self.near > 0.0 && self.near.is_finite()) {
+            return Err(error(
+                &["near"],
+                format!("near: expected a positive distance, found {}", self.near),
+            ));
+        }
+        if !(self.far > self.near && self.far.is_finite()) {
+            return Err(error(
+                &["near", "far"],
+                format!(
+                    "far: expected a distance beyond near {}, found {}",
+                    self.near, self.far
+                ),
+            ));
+        }
+        match self.orthographic {
+            Some(height) if !(height > 0.0 && height.is_finite()) => {
+                return Err(error(
+                    &["orthographic"],
+                    format!("orthographic: expected a positive height, found {}", height),
+                ))
+            }
+            None if !(self.fov > 0.0 && self.fov < 180.0) => {
+                return Err(error(
+                    &["fov"],
+                    format!(
+                        "fov: expected an angle between 0 and 180, found {}",
+                        self.fov
+                    ),
+                ))
+            }
+            _ => (),
+        }
+        let projection = Projection::Perspective {
+            fovy: self.fov.to_radians(),
+            aspect: 1.0,
+            near: self.near,
+            far: self.far,
+        };
+        if Camera::look_at(self.eye, self.target, self.up, projection).is_degenerate() {
+            return Err(error(
+                &["eye", "target", "up"],
+                "the eye must differ from the target and up must not point along the view"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn light_property(&mut self, key: &str, args: &[&str]) -> Result<(), Error> {
+        match key {
+            "direction" => {
+                let direction = self.vector(key, args)?;
+                if direction.length() == 0.0 {
+                    return Err(self.error("direction: must not be zero"));
+                }
+                self.scene.light_dir = direction;
+            }
+            "shadows" => self.scene.shadows = self.switch(key, args)?,
+            _ => return Err(self.unknown("light", key)),
+        }
+        Ok(())
+    }
+
+    fn material_property(&mut self, key: &str, rest: &str, args: &[&str]) -> Result<(), Error> {
+        let line = self.line;
+
+        match key {
+            "color" => {
+                let color = self.color(key, args)?;
+                self.spec().material.color = color;
+            }
+            "ambient" => {
+                let k = self.number(key, self.value(key, args)?)?;
+                self.spec().material.lighting.ambient = k;
+            }
+            "diffuse" => {
+                let k = self.number(key, self.value(key, args)?)?;
+                self.spec().material.lighting.diffuse = k;
+            }
+            "specular" => {
+                let k = self.number(key, self.value(key, args)?)?;
+                self.spec().material.lighting.specular = k;
+            }
+            "shininess" => {
+                let n = self.number(key, self.value(key, args)?)?;
+                self.spec().material.lighting.shininess = n;
+            }
+            "highlights" => {
+                let specular = match self.value(key, args)? {
+                    "phong" => Specular::Phong,
+                    "blinn-phong" => Specular::BlinnPhong,
+                    value => {
+                        return Err(self.error(format!(
+                            "highlights: expected phong or blinn-phong, found '{}'",
+                            value
+                        )))
+                    }
+                };
+                self.spec().material.specular = specular;
+            }
+            "texture" => {
+                let path = self.path(key, rest)?;
+                self.spec().texture = Some((line, path));
+            }
+            "normal-map" => {
+                let path = self.path(key, rest)?;
+                self.spec().normal_map = Some((line, path));
+            }
+            "filter" => {
+                let filter = match self.value(key, args)? {
+                    "nearest" => TextureFilter::Nearest,
+                    "bilinear" => TextureFilter::Bilinear,
+                    "trilinear" => TextureFilter::Trilinear,
+                    "anisotropic" => TextureFilter::Anisotropic(8),
+                    value => return Err(self.error(format!("unknown texture filter '{}'", value))),
+                };
+                self.spec().filter = filter;
+            }
+            _ => return Err(self.unknown("material", key)),
+        }
+        Ok(())
+    }
+
+    // the material of the current section.
+    fn spec(&mut self) -> &mut MaterialSpec {
+        self.material.as_mut().expect("inside a material section")
+    }
+
+    fn node_property(
+        &mut self,
+        index: usize,
+        key: &str,
+        rest: &str,
+        args: &[&str],
+    ) -> Result<(), Error> {
+        match key {
+            "mesh" => {
+                if self.nodes[index].children.iter().any(|c| c.mesh.is_some()) {
+                    return Err(self.error("the node already has a mesh"));
+                }
+                let path = self.path(key, rest)?;
+                let models = self.load_mesh(&path)?;
+                self.nodes[index].children.extend(models);
+            }
+            "parent" => {
+                let name = self.value(key, args)?;
+                let parent = match self.names.get(name) {
+                    Some(&parent) if parent != index => parent,
+                    Some(_) => return Err(self.error("a node cannot be its own parent")),
+                    None => return Err(self.error(format!("unknown node '{}'", name))),
+                };
+                self.parents[index] = Some(parent);
+            }
+            "material" => {
+                let name = self.value(key, args)?;
+                match self.materials.get(name) {
+                    Some(material) => self.nodes[index].material = Some(Arc::clone(material)),
+                    None => return Err(self.error(format!("unknown material '{}'", name))),
+                }
+            }
+            "translate" => {
+                let offset = self.vector(key, args)?;
+                self.transform(index, Matrix4x4::translation(offset));
+            }
+            "rotate" => {
+                let v = self.numbers(key, args, 4)?;
+                let axis = Point3D::new([v[0], v[1], v[2]]);
+                if axis.length() == 0.0 {
+                    return Err(self.error("rotate: the axis must not be zero"));
+                }
+                self.transform(index, Matrix4x4::rotation(axis, v[3].to_radians()));
+            }
+            "scale" => {
+                let factors = if args.len() == 1 {
+                    let s = self.number(key, args[0])?;
+                    Point3D::new([s, s, s])
+                } else {
+                    self.vector(key, args)?
+                };
+                self.transform(index, Matrix4x4::scale(factors));
+            }
+            _ => return Err(self.unknown("node", key)),
+        }
+        Ok(())
+    }
+
+    // apply `m` after the transforms the node already has.
+    fn transform(&mut self, index: usize, m: Matrix4x4) {
+        let node = &mut self.nodes[index];
+        node.transform = m * node.transform;
+    }
+
+    // one node per model of the OBJ file at `path`. Each file is loaded
+    // once; nodes using it again share its meshes.
+    fn load_mesh(&mut self, path: &Path) -> Result<Vec<Node>, Error> {
+        if !self.loaded.contains_key(path) {
+            let (meshes, _) = Mesh::load_obj(path).map_err(|err| Error::Load {
+                line: self.line,
+                path: path.to_path_buf(),
+                source: Box::new(err),
+            })?;
+
+            let mut models = vec![];
+            for mesh in meshes {
+                let name = mesh.name.clone();
+                models.push((self.scene.add_mesh(mesh), name));
+            }
+            self.loaded.insert(path.to_path_buf(), models);
+        }
+
+        Ok(self.loaded[path]
+            .iter()
+            .map(|&(index, ref name)| {
+                let mut node = Node::new(name);
+                node.mesh = Some(index);
+                node
+            })
+            .collect())
+    }
+
+    fn unknown(&self, section: &str, key: &str) -> Error {
+        self.error(format!("unknown {} property '{}'", section, key))
+    }
+
+    // the single value of a property.
+    fn value<'b>(&self, key: &str, args: &[&'b str]) -> Result<&'b str, Error> {
+        if args.len() != 1 {
+            return Err(self.error(format!("{}: expected one value, found {}", key, args.len())));
+        }
+        Ok(args[0])
+    }
+
+    fn number<T: FromStr>(&self, key: &str, value: &str) -> Result<T, Error> {
+        value
+            .parse()
+            .map_err(|_| self.error(format!("{}: '{}' is not a number", key, value)))
+    }
+
+    fn numbers(&self, key: &str, args: &[&str], count: usize) -> Result<Vec<f32>, Error> {
+        if args.len() != count {
+            return Err(self.error(format!(
+                "{}: expected {} numbers, found {}",
+                key,
+                count,
+                args.len()
+            )));
+        }
+        args.iter().map(|value| self.number(key, value)).collect()
+    }
+
+    fn vector(&self, key: &str, args: &[&str]) -> Result<Point3D, Error> {
+        let v = self.numbers(key, args, 3)?;
+        Ok(Point3D::new([v[0], v[1], v[2]]))
+    }
+
+    fn color(&self, key: &str, args: &[&str]) -> Result<RGBPixel, Error> {
+        let v = self.numbers(key, args, 3)?;
+        if v.iter().any(|&c| !(0.0..=255.0).contains(&c)) {
+            return Err(self.error(format!("{}: channels go from 0 to 255", key)));
+        }
+        Ok(RGBPixel {
+            red: v[0].round() as u8,
+            green: v[1].round() as u8,
+            blue: v[2].round() as u8,
+        })
+    }
+
+    fn switch(&self, key: &str, args: &[&str]) -> Result<bool, Error> {
+        match self.value(key, args)? {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            value => Err(self.error(format!("{}: expected on or off, found '{}'", key, value))),
+        }
+    }
+
+    // a path running to the end of the line, relative to the scene file.
+    fn path(&self, key: &str, rest: &str) -> Result<PathBuf, Error> {
+        if rest.is_empty() {
+            return Err(self.error(format!("{}: expected a path", key)));
+        }
+        Ok(self.dir.join(rest))
+    }
+
+    fn finish(mut self) -> Result<SceneFile, Error> {
+        self.end_section()?;
+
+        let format = match self.format.or_else(|| Format::from_path(&self.output_path)) {
+            Some(format) => format,
+            None => {
+                return Err(Error::Parse {
+                    line: self.output_line,
+                    message: format!(
+                        "cannot tell the format of '{}', add a format line",
+                        self.output_path.display()
+                    ),
+                })
+            }
+        };
+
+        // parents come before their children, so going backwards every node
+        // is complete by the time it is moved under its parent.
+        let mut children = self
+            .nodes
+            .iter()
+            .map(|_| vec![])
+            .collect::<Vec<Vec<Node>>>();
+        let mut roots = vec![];
+        while let Some(mut node) = self.nodes.pop() {
+            let index = self.nodes.len();
+            let mut own = children.pop().expect("one list per node");
+            own.reverse();
+            node.children.extend(own);
+
+            match self.parents[index] {
+                Some(parent) => children[parent].push(node),
+                None => roots.push(node),
+            }
+        }
+        roots.reverse();
+        self.scene.root.children.extend(roots);
+
+        let aspect = self.width as f32 / self.height as f32;
+        let projection = match self.orthographic {
+            Some(height) => Projection::Orthographic {
+                left: -0.5 * height * aspect,
+                right: 0.5 * height * aspect,
+                bottom: -0.5 * height,
+                top: 0.5 * height,
+                near: self.near,
+                far: self.far,
+            },
+            None => Projection::Perspective {
+                fovy: self.fov.to_radians(),
+                aspect,
+                near: self.near,
+                far: self.far,
+            },
+        };
+
+        Ok(SceneFile {
+            scene: self.scene,
+            camera: Camera::look_at(self.eye, self.target, self.up, projection),
+            output: Output {
+                path: self.output_path,
+                format,
+                width: self.width,
+                height: self.height,
+                multisample: self.multisample,
+                ssaa: self.ssaa,
+                threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            },
+        })
+    }
+}
+
+// `Texture::load_with_filter`, failing with the line that named the file.
+fn load_filtered(line: usize, path: &Path, filter: TextureFilter) -> Result<Texture, Error> {
+    Texture::load_with_filter(path, filter).map_err(|err| Error::Load {
+        line,
+        path: path.to_path_buf(),
+        source: Box::new(err),
+    })
+}
@@ -0,0 +1,145 @@
+use geometry::{Point, Point3D};
+use vector::{Vec2, Vec3};
+use RGBPixel;
+
+/*
+ * Programmable stages of the rasterizer. `ImageMeta::draw_mesh` feeds every
+ * vertex of a mesh through `Shader::vertex`, rasterizes the resulting
+ * triangles, and calls `Shader::fragment` with the varyings interpolated at
+ * each covered pixel.
+ */
+
+/**
+ * Per-vertex attributes handed to the vertex stage.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct Vertex {
+    pub face: usize,
+    pub nthvert: usize,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub texcoord: Vec2,
+    // zero when the mesh has no texture coordinates to derive them from.
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+    // unit normal of the face the vertex belongs to; handy for flat shading.
+    pub face_normal: Vec3,
+}
+
+/**
+ * Anything the vertex stage emits for interpolation across a triangle.
+ * `weights` are the barycentric coordinates of the fragment, they sum to 1.
+ *
+ * Interpolation must be linear in the weights: the rasterizer also passes
+ * differences of weights, summing to 0, to get the difference between the
+ * varyings of two pixels.
+ */
+pub trait Varying: Copy + Send + Sync {
+    fn interpolate(values: &[Self; 3], weights: Point3D) -> Self;
+}
+
+pub trait Shader: Sync {
+    type Varying: Varying;
+
+    /**
+     * Transform a vertex into clip coordinates (x, y, z, w) and emit its
+     * varyings. After division by w the visible volume is [-1, 1]^3, with the
+     * near plane at z = -1 and the far plane at z = 1.
+     */
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], Self::Varying);
+
+    /**
+     * Shade a fragment from its interpolated varyings. Returning `None`
+     * discards the fragment; neither the color nor the depth buffer is written.
+     */
+    fn fragment(&self, varying: Self::Varying) -> Option<RGBPixel>;
+
+    /**
+     * Whether the rasterizer should call `fragment_with_derivatives` rather
+     * than `fragment`. Working out the derivatives costs two more
+     * interpolations per fragment.
+     */
+    const USES_DERIVATIVES: bool = false;
+
+    /**
+     * Shade a fragment knowing how its varyings change from one pixel to
+     * the next, e.g. to pick a mipmap level. Only called when
+     * `USES_DERIVATIVES` is set.
+     */
+    fn fragment_with_derivatives(
+        &self,
+        varying: Self::Varying,
+        _derivatives: Derivatives<Self::Varying>,
+    ) -> Option<RGBPixel> {
+        self.fragment(varying)
+    }
+}
+
+/**
+ * Screen space derivatives of the varyings: how much they change one pixel
+ * to the right and one pixel up. Like on a GPU they are measured across the
+ * 2x2 quad of pixels holding the fragment, from the pixel centers in the
+ * lower left corner of the quad to those next to it, so all four pixels of
+ * a quad share them. Neighbours outside the triangle still count; their
+ * varyings are extrapolated.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct Derivatives<V> {
+    pub dx: V,
+    pub dy: V,
+}
+
+impl Varying for () {
+    fn interpolate(_values: &[(); 3], _weights: Point3D) {}
+}
+
+impl Varying for f32 {
+    fn interpolate(values: &[f32; 3], weights: Point3D) -> f32 {
+        weights.x * values[0] + weights.y * values[1] + weights.z * values[2]
+    }
+}
+
+impl Varying for Point {
+    fn interpolate(values: &[Point; 3], weights: Point3D) -> Point {
+        Point {
+            x: weights.x * values[0].x + weights.y * values[1].x + weights.z * values[2].x,
+            y: weights.x * values[0].y + weights.y * values[1].y + weights.z * values[2].y,
+        }
+    }
+}
+
+impl Varying for Point3D {
+    fn interpolate(values: &[Point3D; 3], weights: Point3D) -> Point3D {
+        weights.x * values[0] + weights.y * values[1] + weights.z * values[2]
+    }
+}
+
+impl<A: Varying, B: Varying> Varying for (A, B) {
+    fn interpolate(values: &[(A, B); 3], weights: Point3D) -> (A, B) {
+        (
+            A::interpolate(&[values[0].0, values[1].0, values[2].0], weights),
+            B::interpolate(&[values[0].1, values[1].1, values[2].1], weights),
+        )
+    }
+}
+
+impl<A: Varying, B: Varying, C: Varying> Varying for (A, B, C) {
+    fn interpolate(values: &[(A, B, C); 3], weights: Point3D) -> (A, B, C) {
+        (
+            A::interpolate(&[values[0].0, values[1].0, values[2].0], weights),
+            B::interpolate(&[values[0].1, values[1].1, values[2].1], weights),
+            C::interpolate(&[values[0].2, values[1].2, values[2].2], weights),
+        )
+    }
+}
+
+impl<A: Varying, B: Varying, C: Varying, D: Varying> Varying for (A, B, C, D) {
+    fn interpolate(values: &[(A, B, C, D); 3], weights: Point3D) -> (A, B, C, D) {
+        (
+            A::interpolate(&[values[0].0, values[1].0, values[2].0], weights),
+            B::interpolate(&[values[0].1, values[1].1, values[2].1], weights),
+            C::interpolate(&[values[0].2, values[1].2, values[2].2], weights),
+            D::interpolate(&[values[0].3, values[1].3, values[2].3], weights),
+        )
+    }
+}
@@ -0,0 +1,360 @@
+use camera::Camera;
+use error::Error;
+use geometry::Point3D;
+use matrix::Matrix4x4;
+use shader::{Derivatives, Shader, Vertex};
+use shadow::ShadowMap;
+use std::sync::Arc;
+use texture::{Rgba, Texture};
+use vector::{Vec2, Vec3};
+use RGBPixel;
+
+/*
+ * Stock shading models for `ImageMeta::draw_mesh`.
+ *
+ * `transform` takes model coordinates to clip space, e.g. a camera's
+ * `view_projection_matrix`. `light_dir` is the world space direction the
+ * light travels in, so a surface facing the light has a normal pointing
+ * against it.
+ */
+
+fn lambert(normal: Vec3, light_dir: Point3D) -> f32 {
+    (-normal.dot(light_dir.into())).max(0.0)
+}
+
+fn to_clip(transform: &Matrix4x4, p: Vec3) -> [f32; 4] {
+    (*transform * p.to_homogeneous()).into()
+}
+
+/**
+ * One light intensity per face, from the face normal.
+ */
+pub struct FlatShader {
+    pub transform: Matrix4x4,
+    pub light_dir: Point3D,
+    pub color: RGBPixel,
+}
+
+impl Shader for FlatShader {
+    type Varying = f32;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], f32) {
+        (
+            to_clip(&self.transform, vertex.position),
+            lambert(vertex.face_normal, self.light_dir),
+        )
+    }
+
+    fn fragment(&self, intensity: f32) -> Option<RGBPixel> {
+        Some(self.color.scale(intensity))
+    }
+}
+
+/**
+ * Light intensity computed at the vertices from the vertex normals and
+ * interpolated across the face.
+ */
+pub struct GouraudShader {
+    pub transform: Matrix4x4,
+    pub light_dir: Point3D,
+    pub color: RGBPixel,
+}
+
+impl Shader for GouraudShader {
+    type Varying = f32;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], f32) {
+        (
+            to_clip(&self.transform, vertex.position),
+            lambert(vertex.normal, self.light_dir),
+        )
+    }
+
+    fn fragment(&self, intensity: f32) -> Option<RGBPixel> {
+        Some(self.color.scale(intensity))
+    }
+}
+
+/**
+ * Diffuse texture modulated by a per-face light intensity, as in the flat
+ * shader.
+ */
+pub struct TextureShader {
+    pub transform: Matrix4x4,
+    pub light_dir: Point3D,
+    pub texture: Texture,
+}
+
+impl Shader for TextureShader {
+    type Varying = (Vec2, f32);
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], (Vec2, f32)) {
+        (
+            to_clip(&self.transform, vertex.position),
+            (vertex.texcoord, lambert(vertex.face_normal, self.light_dir)),
+        )
+    }
+
+    fn fragment(&self, (uv, intensity): (Vec2, f32)) -> Option<RGBPixel> {
+        Some(self.texture.sample(uv.into()).to_pixel().scale(intensity))
+    }
+
+    const USES_DERIVATIVES: bool = true;
+
+    fn fragment_with_derivatives(
+        &self,
+        (uv, intensity): (Vec2, f32),
+        derivatives: Derivatives<(Vec2, f32)>,
+    ) -> Option<RGBPixel> {
+        let texel =
+            self.texture
+                .sample_grad(uv.into(), derivatives.dx.0.into(), derivatives.dy.0.into());
+        Some(texel.to_pixel().scale(intensity))
+    }
+}
+
+/**
+ * Surface response to light for `PhongShader`. The ambient, diffuse and
+ * specular terms are weights in [0, 1]; shininess is the exponent of the
+ * specular lobe, larger values give smaller and sharper highlights.
+ */
+#[derive(Debug, Copy, Clone)]
+pub struct Material {
+    pub ambient: f32,
+    pub diffuse: f32,
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Default for Material {
+    fn default() -> Material {
+        Material {
+            ambient: 0.1,
+            diffuse: 0.8,
+            specular: 0.5,
+            shininess: 32.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Specular {
+    // reflect the light about the normal and compare with the view direction.
+    Phong,
+    // compare the normal with the halfway vector between light and view.
+    BlinnPhong,
+}
+
+/**
+ * A normal map stores a unit normal per texel, each coordinate mapped from
+ * [-1, 1] to a color channel in [0, 255].
+ */
+pub enum NormalMap {
+    // normals in model coordinates; they replace the mesh normals outright.
+    ObjectSpace(Texture),
+    // normals relative to the surface: x along the tangent, y along the
+    // bitangent and z along the interpolated vertex normal.
+    TangentSpace(Texture),
+}
+
+/**
+ * Per-fragment lighting. Normals and positions are interpolated in view
+ * space, where the eye sits at the origin; the world space light direction
+ * is brought into view space with the camera's view matrix. The mesh is
+ * placed in the world by `set_model`, the identity until it is called.
+ *
+ * The base color comes from `texture` when one is set, otherwise from
+ * `color`. Specular highlights are white. A `normal_map` perturbs the
+ * interpolated normal per fragment. With a `shadow` map, fragments hidden
+ * from the light keep only the ambient term.
+ */
+pub struct PhongShader {
+    pub material: Material,
+    pub specular: Specular,
+    pub color: RGBPixel,
+    pub texture: Option<Arc<Texture>>,
+    pub normal_map: Option<Arc<NormalMap>>,
+    pub shadow: Option<ShadowMap>,
+    // model to world space.
+    model: Matrix4x4,
+    // model to view space, for positions and for normals.
+    model_view: Matrix4x4,
+    normal_matrix: Matrix4x4,
+    view: Matrix4x4,
+    projection: Matrix4x4,
+    // direction towards the light, in view space.
+    to_light: Vec3,
+}
+
+impl PhongShader {
+    pub fn new(camera: &Camera, light_dir: Point3D) -> PhongShader {
+        let view = camera.view_matrix();
+        let light = view * Vec3::from(light_dir).extend(0.0);
+
+        PhongShader {
+            material: Material::default(),
+            specular: Specular::BlinnPhong,
+            color: RGBPixel {
+                red: 255,
+                green: 255,
+                blue: 255,
+            },
+            texture: None,
+            normal_map: None,
+            shadow: None,
+            model: Matrix4x4::identity(),
+            model_view: view,
+            normal_matrix: view,
+            view,
+            projection: camera.projection_matrix(),
+            to_light: -light.truncate().normalized(),
+        }
+    }
+}
+
+impl PhongShader {
+    /**
+     * Draw the following meshes transformed by `model`, from their own
+     * coordinates to world space, so a mesh placed in several spots need
+     * not be copied into each. Fails when `model` has no inverse to carry
+     * the normals with.
+     */
+    pub fn set_model(&mut self, model: Matrix4x4) -> Result<(), Error> {
+        self.model_view = self.view * model;
+        self.normal_matrix = self.model_view.inverse_transpose()?;
+        self.model = model;
+        Ok(())
+    }
+
+    // a model space normal in view space.
+    fn to_view(&self, normal: Vec3) -> Vec3 {
+        (self.normal_matrix * normal.extend(0.0)).truncate()
+    }
+
+    // a model space direction along the surface, such as a tangent, as a
+    // unit vector in view space; zero stays zero.
+    fn along_surface(&self, direction: Vec3) -> Vec3 {
+        let d = (self.model_view * direction.extend(0.0)).truncate();
+        if d.length() > 0.0 {
+            d.normalized()
+        } else {
+            d
+        }
+    }
+
+    // unit view space normal of a fragment; `(uv, dx, dy)` are its texture
+    // coordinates and their derivatives.
+    fn normal(
+        &self,
+        normal: Vec3,
+        (tangent, bitangent): (Vec3, Vec3),
+        (uv, dx, dy): (Vec2, Vec2, Vec2),
+    ) -> Vec3 {
+        let n =
+            match self.normal_map.as_deref() {
+                None => normal,
+                Some(NormalMap::ObjectSpace(ref map)) => self.to_view(decode_normal(
+                    map.sample_grad(uv.into(), dx.into(), dy.into()),
+                )),
+                Some(NormalMap::TangentSpace(ref map)) => {
+                    let ts = decode_normal(map.sample_grad(uv.into(), dx.into(), dy.into()));
+                    ts[0] * tangent + ts[1] * bitangent + ts[2] * normal.normalized()
+                }
+            };
+        n.normalized()
+    }
+}
+
+impl Shader for PhongShader {
+    // view space normal, view and world space position, texture coordinates
+    // and view space tangent and bitangent.
+    type Varying = (Vec3, (Vec3, Vec3), Vec2, (Vec3, Vec3));
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], Self::Varying) {
+        let p = vertex.position.to_homogeneous();
+        let position = self.model_view * p;
+
+        (
+            (self.projection * position).into(),
+            (
+                self.to_view(vertex.normal),
+                (position.truncate(), (self.model * p).truncate()),
+                vertex.texcoord,
+                (
+                    self.along_surface(vertex.tangent),
+                    self.along_surface(vertex.bitangent),
+                ),
+            ),
+        )
+    }
+
+    fn fragment(&self, varying: Self::Varying) -> Option<RGBPixel> {
+        self.shade(varying, (Vec2::zero(), Vec2::zero()))
+    }
+
+    const USES_DERIVATIVES: bool = true;
+
+    fn fragment_with_derivatives(
+        &self,
+        varying: Self::Varying,
+        derivatives: Derivatives<Self::Varying>,
+    ) -> Option<RGBPixel> {
+        self.shade(varying, (derivatives.dx.2, derivatives.dy.2))
+    }
+}
+
+impl PhongShader {
+    // `dx` and `dy` are the derivatives of the texture coordinates.
+    fn shade(
+        &self,
+        (normal, (position, world), uv, frame): <Self as Shader>::Varying,
+        (dx, dy): (Vec2, Vec2),
+    ) -> Option<RGBPixel> {
+        let n = self.normal(normal, frame, (uv, dx, dy));
+        let visibility = self
+            .shadow
+            .as_ref()
+            .map_or(1.0, |map| map.visibility(world.into()));
+
+        let to_eye = -position.normalized();
+
+        let l = self.to_light;
+        let n_dot_l = n.dot(l);
+        let diffuse = n_dot_l.max(0.0);
+
+        let specular = if n_dot_l <= 0.0 {
+            0.0
+        } else {
+            let alignment = match self.specular {
+                Specular::Phong => {
+                    let reflected = (2.0 * n_dot_l) * n - l;
+                    reflected.dot(to_eye)
+                }
+                Specular::BlinnPhong => n.dot((l + to_eye).normalized()),
+            };
+            alignment.max(0.0).powf(self.material.shininess)
+        };
+
+        let base = match self.texture {
+            Some(ref texture) => texture
+                .sample_grad(uv.into(), dx.into(), dy.into())
+                .to_pixel(),
+            None => self.color,
+        };
+        let intensity = self.material.ambient + visibility * self.material.diffuse * diffuse;
+        let highlight = visibility * 255.0 * self.material.specular * specular;
+
+        let channel = |c: u8| (c as f32 * intensity + highlight).min(255.0) as u8;
+
+        Some(RGBPixel {
+            red: channel(base.red),
+            green: channel(base.green),
+            blue: channel(base.blue),
+        })
+    }
+}
+
+// map color channels in [0, 1] back to coordinates in [-1, 1].
+fn decode_normal(color: Rgba) -> Vec3 {
+    Vec3::new(color.red, color.green, color.blue) * 2.0 - 1.0
+}
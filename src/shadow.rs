@@ -0,0 +1,206 @@
+use camera::{Camera, Projection};
+use error::Error;
+use geometry::Point3D;
+use matrix::Matrix4x4;
+use mesh::Mesh;
+use shader::{Shader, Vertex};
+use {ImageMeta, RGBPixel};
+
+/*
+ * Shadow mapping in two passes. The first pass renders the scene from the
+ * light with a depth-only shader and keeps the resulting z-buffer; the main
+ * pass projects each fragment into the light's image and compares its
+ * depth with the stored one. A fragment further from the light than the
+ * nearest surface it sees is in shadow.
+ *
+ * Depths follow the z-buffer convention of `Camera::viewport_matrix`: they
+ * lie in [0, 1] and larger values are closer to the light.
+ */
+pub struct ShadowMap {
+    // world to the light's clip space.
+    pub transform: Matrix4x4,
+    pub depth: Vec<f32>,
+    pub width: usize,
+    pub height: usize,
+    // added to a fragment's depth before the comparison, moving it towards
+    // the light (larger is closer), so a surface does not shadow itself
+    // through rounding ("shadow acne").
+    pub bias: f32,
+    // radius in texels of the percentage-closer filter; `None` gives hard
+    // shadows from a single texel.
+    pub pcf: Option<usize>,
+}
+
+/**
+ * Depth-only first pass: the z-buffer is all that is kept.
+ */
+pub(crate) struct DepthShader {
+    pub transform: Matrix4x4,
+}
+
+impl Shader for DepthShader {
+    type Varying = ();
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], ()) {
+        let clip = self.transform * vertex.position.to_homogeneous();
+        (clip.into(), ())
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        Some(RGBPixel {
+            red: 0,
+            green: 0,
+            blue: 0,
+        })
+    }
+}
+
+impl ShadowMap {
+    /**
+     * Render the depth of `meshes` as seen from `light`. An orthographic
+     * projection models a directional light, a perspective one a spot light.
+     */
+    pub fn render(
+        light: &Camera,
+        meshes: &[Mesh],
+        width: usize,
+        height: usize,
+    ) -> Result<ShadowMap, Error> {
+        ShadowMap::render_instances(light, &in_place(meshes), width, height)
+    }
+
+    /**
+     * `render` for meshes placed in the world by the matrix paired with
+     * each, from their own coordinates.
+     */
+    pub fn render_instances(
+        light: &Camera,
+        instances: &[(&Mesh, Matrix4x4)],
+        width: usize,
+        height: usize,
+    ) -> Result<ShadowMap, Error> {
+        let transform = light.view_projection_matrix();
+        let mut image = ImageMeta::new(width, height)?;
+
+        for &(mesh, model) in instances.iter() {
+            let mut shader = DepthShader {
+                transform: transform * model,
+            };
+            image.draw_mesh(mesh, &mut shader);
+        }
+
+        Ok(ShadowMap {
+            transform,
+            depth: image.zbuffer,
+            width,
+            height,
+            bias: 0.005,
+            pcf: None,
+        })
+    }
+
+    /**
+     * A directional light as an orthographic camera looking along
+     * `light_dir`, sized to the bounding sphere of the meshes.
+     */
+    pub fn light_camera(meshes: &[Mesh], light_dir: Point3D) -> Camera {
+        ShadowMap::light_camera_instances(&in_place(meshes), light_dir)
+    }
+
+    /**
+     * `light_camera` for meshes placed in the world as in
+     * `render_instances`.
+     */
+    pub fn light_camera_instances(instances: &[(&Mesh, Matrix4x4)], light_dir: Point3D) -> Camera {
+        let positions = || {
+            instances.iter().flat_map(|&(mesh, model)| {
+                mesh.positions.iter().map(move |p| {
+                    let v = model * [p.x, p.y, p.z, 1.0];
+                    Point3D::new([v[0] / v[3], v[1] / v[3], v[2] / v[3]])
+                })
+            })
+        };
+        let count = instances
+            .iter()
+            .map(|&(mesh, _)| mesh.positions.len())
+            .sum::<usize>()
+            .max(1);
+        let center = positions().fold(Point3D::zero(), |sum, p| sum + p) / count as f32;
+        let radius = positions()
+            .map(|p| (p - center).length())
+            .fold(0.0_f32, f32::max)
+            .max(1e-3);
+
+        // any up vector not parallel to the light will do.
+        let up = if light_dir.y.abs() < 0.9 {
+            Point3D::new([0.0, 1.0, 0.0])
+        } else {
+            Point3D::new([1.0, 0.0, 0.0])
+        };
+
+        Camera::look_at(
+            center - 2.0 * radius * light_dir,
+            center,
+            up,
+            Projection::Orthographic {
+                left: -radius,
+                right: radius,
+                bottom: -radius,
+                top: radius,
+                near: 0.5 * radius,
+                far: 3.5 * radius,
+            },
+        )
+    }
+
+    /**
+     * Fraction of light reaching a world space position: 1 when lit, 0 when
+     * fully in shadow and in between on the edge of a filtered shadow.
+     * Positions outside the light's view are treated as lit.
+     */
+    pub fn visibility(&self, position: Point3D) -> f32 {
+        let clip = self.transform * [position.x, position.y, position.z, 1.0];
+        if clip[3] <= 0.0 {
+            return 1.0;
+        }
+
+        let v = Camera::viewport_matrix(self.width as f32, self.height as f32) * clip;
+        let (x, y, z) = (v[0] / v[3], v[1] / v[3], v[2] / v[3]);
+
+        if x < 0.0 || y < 0.0 || x > self.width as f32 || y > self.height as f32 {
+            return 1.0;
+        }
+
+        // same layout as `ImageMeta`: top row first, origin in the lower
+        // left-hand corner.
+        let col = (x.floor() as usize).min(self.width - 1) as isize;
+        let row =
+            ((self.height as f32 - y).floor().max(0.0) as usize).min(self.height - 1) as isize;
+
+        let radius = self.pcf.unwrap_or(0) as isize;
+        let mut lit = 0;
+        let mut taps = 0;
+
+        for dr in -radius..=radius {
+            for dc in -radius..=radius {
+                let r = (row + dr).max(0).min(self.height as isize - 1) as usize;
+                let c = (col + dc).max(0).min(self.width as isize - 1) as usize;
+
+                if z + self.bias >= self.depth[r * self.width + c] {
+                    lit += 1;
+                }
+                taps += 1;
+            }
+        }
+
+        lit as f32 / taps as f32
+    }
+}
+
+// meshes already in world space.
+fn in_place(meshes: &[Mesh]) -> Vec<(&Mesh, Matrix4x4)> {
+    meshes
+        .iter()
+        .map(|mesh| (mesh, Matrix4x4::identity()))
+        .collect()
+}
@@ -0,0 +1,440 @@
+use error::Error;
+use geometry::Point;
+use imagefmt::{ColFmt, Image};
+use std::path::Path;
+use {load_texture, RGBPixel};
+
+/*
+ * Textures sampled in normalized UV space: (0, 0) is the lower left corner
+ * of the image and (1, 1) the upper right one, whatever its size. Texel
+ * (i, j), counting rows from the top, covers [i, i + 1] x [j, j + 1] in
+ * texel space, so its center sits at ((i + 0.5) / width, 1 - (j + 0.5) /
+ * height) in UV space.
+ */
+
+/**
+ * A filtered color with channels in [0, 1]. Textures without an alpha
+ * channel are opaque; grayscale ones repeat the gray level in red, green
+ * and blue.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Rgba {
+    /**
+     * The color rounded to bytes, dropping alpha. Channels outside [0, 1]
+     * saturate.
+     */
+    pub fn to_pixel(self) -> RGBPixel {
+        let byte = |c: f32| (c * 255.0).round() as u8;
+
+        RGBPixel {
+            red: byte(self.red),
+            green: byte(self.green),
+            blue: byte(self.blue),
+        }
+    }
+
+    fn lerp(self, other: Rgba, t: f32) -> Rgba {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+
+        Rgba {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+            alpha: mix(self.alpha, other.alpha),
+        }
+    }
+}
+
+/**
+ * Layout of the texels, one byte per channel.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFormat {
+    Gray,
+    Rgb,
+    Rgba,
+}
+
+impl TextureFormat {
+    pub fn channels(self) -> usize {
+        match self {
+            TextureFormat::Gray => 1,
+            TextureFormat::Rgb => 3,
+            TextureFormat::Rgba => 4,
+        }
+    }
+}
+
+/**
+ * How texture coordinates outside [0, 1] are brought back onto the image.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Wrap {
+    // tile the image.
+    Repeat,
+    // stretch the outermost texels.
+    ClampToEdge,
+    // tile the image, flipping every other copy.
+    MirroredRepeat,
+    // a constant color around the image.
+    Border(Rgba),
+}
+
+impl Wrap {
+    // where texel index `i` lands along an axis of `n` texels, or `None`
+    // when it falls on the border.
+    fn index(self, i: i64, n: usize) -> Option<usize> {
+        let n = n as i64;
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::ClampToEdge => i.clamp(0, n - 1),
+            Wrap::MirroredRepeat => {
+                let i = i.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+            Wrap::Border(_) => {
+                if i < 0 || i >= n {
+                    return None;
+                }
+                i
+            }
+        };
+        Some(i as usize)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFilter {
+    // the texel under the coordinate.
+    Nearest,
+    // a weighted average of the four texels whose centers surround it.
+    Bilinear,
+    // bilinear lookups in the two mipmap levels closest to the pixel's
+    // footprint, blended by how close each one is.
+    Trilinear,
+    // up to this many trilinear lookups spread along the long axis of the
+    // footprint, in the level matching its short axis.
+    Anisotropic(usize),
+}
+
+/**
+ * One image of the mip chain, top row first.
+ */
+struct Level {
+    width: usize,
+    height: usize,
+    texels: Vec<u8>,
+}
+
+impl Level {
+    // the next level down: half the size, each texel the average of a 2x2
+    // block. An odd last row or column is dropped.
+    fn half(&self, channels: usize) -> Level {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height * channels);
+
+        for j in 0..height {
+            for i in 0..width {
+                let cols = [2 * i, (2 * i + 1).min(self.width - 1)];
+                let rows = [2 * j, (2 * j + 1).min(self.height - 1)];
+
+                for k in 0..channels {
+                    let mut sum = 0;
+                    for &row in rows.iter() {
+                        for &col in cols.iter() {
+                            sum += self.texels[channels * (row * self.width + col) + k] as u32;
+                        }
+                    }
+                    texels.push(((sum + 2) / 4) as u8);
+                }
+            }
+        }
+
+        Level {
+            width,
+            height,
+            texels,
+        }
+    }
+}
+
+/**
+ * An image sampled in UV space. Mipmaps, smaller copies of the image
+ * halving its size down to a single texel, are only made by
+ * `generate_mipmaps`; without them the trilinear and anisotropic filters
+ * have just the full size image to read and act like bilinear filtering.
+ */
+pub struct Texture {
+    format: TextureFormat,
+    // the full size image first.
+    levels: Vec<Level>,
+    pub wrap: Wrap,
+    pub filter: TextureFilter,
+}
+
+impl Texture {
+    /**
+     * A texture over `texels`, stored top row first. Sampling defaults to
+     * nearest texels clamped to the edges.
+     */
+    pub fn new(
+        width: usize,
+        height: usize,
+        format: TextureFormat,
+        texels: Vec<u8>,
+    ) -> Result<Texture, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidDimensions { width, height });
+        }
+        let expected = width * height * format.channels();
+        if texels.len() != expected {
+            return Err(Error::TexelCount {
+                expected,
+                found: texels.len(),
+            });
+        }
+
+        Ok(Texture {
+            format,
+            levels: vec![Level {
+                width,
+                height,
+                texels,
+            }],
+            wrap: Wrap::ClampToEdge,
+            filter: TextureFilter::Nearest,
+        })
+    }
+
+    /**
+     * A texture from a decoded image. Gray images stay gray, images with
+     * an alpha channel become RGBA and all others RGB.
+     */
+    pub fn from_image(image: Image<u8>) -> Result<Texture, Error> {
+        let image = match image.fmt {
+            ColFmt::Y | ColFmt::RGB | ColFmt::RGBA => image,
+            ColFmt::YA | ColFmt::AY | ColFmt::BGRA | ColFmt::ARGB | ColFmt::ABGR => {
+                image.convert(ColFmt::RGBA)?
+            }
+            ColFmt::BGR | ColFmt::Auto => image.convert(ColFmt::RGB)?,
+        };
+        let format = match image.fmt {
+            ColFmt::Y => TextureFormat::Gray,
+            ColFmt::RGBA => TextureFormat::Rgba,
+            _ => TextureFormat::Rgb,
+        };
+
+        Texture::new(image.w, image.h, format, image.buf)
+    }
+
+    /**
+     * The image at `path` as a texture sampled with `filter`, with the
+     * mipmaps that filter reads.
+     */
+    pub fn load_with_filter(path: &Path, filter: TextureFilter) -> Result<Texture, Error> {
+        let mut texture = load_texture(path)?;
+        texture.filter = filter;
+        // only the trilinear and anisotropic filters read the smaller levels.
+        if filter != TextureFilter::Nearest && filter != TextureFilter::Bilinear {
+            texture.generate_mipmaps();
+        }
+        Ok(texture)
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /**
+     * Build the mip chain from the full size image, replacing any earlier
+     * one. Each level is a box filtered half of the previous one.
+     */
+    pub fn generate_mipmaps(&mut self) {
+        self.levels.truncate(1);
+        let channels = self.format.channels();
+
+        loop {
+            let next = {
+                let last = &self.levels[self.levels.len() - 1];
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+                last.half(channels)
+            };
+            self.levels.push(next);
+        }
+    }
+
+    /**
+     * Number of mipmap levels, counting the full size image.
+     */
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /**
+     * Width and height of a mipmap level.
+     */
+    pub fn level_size(&self, level: usize) -> (usize, usize) {
+        (self.levels[level].width, self.levels[level].height)
+    }
+
+    /**
+     * Texel (i, j) of a mipmap level, counting rows from the top, after
+     * wrapping.
+     */
+    pub fn texel(&self, level: usize, i: i64, j: i64) -> Rgba {
+        let level = &self.levels[level];
+        let (col, row) = match (
+            self.wrap.index(i, level.width),
+            self.wrap.index(j, level.height),
+        ) {
+            (Some(col), Some(row)) => (col, row),
+            _ => match self.wrap {
+                Wrap::Border(color) => return color,
+                _ => unreachable!("only the border leaves the image"),
+            },
+        };
+
+        let channels = self.format.channels();
+        let idx = channels * (row * level.width + col);
+        let c = |k: usize| level.texels[idx + k] as f32 / 255.0;
+
+        match self.format {
+            TextureFormat::Gray => Rgba {
+                red: c(0),
+                green: c(0),
+                blue: c(0),
+                alpha: 1.0,
+            },
+            TextureFormat::Rgb => Rgba {
+                red: c(0),
+                green: c(1),
+                blue: c(2),
+                alpha: 1.0,
+            },
+            TextureFormat::Rgba => Rgba {
+                red: c(0),
+                green: c(1),
+                blue: c(2),
+                alpha: c(3),
+            },
+        }
+    }
+
+    /**
+     * The color at `uv` with this texture's wrap mode. Without derivatives
+     * the footprint is taken to be a point, so every filter reads the full
+     * size image.
+     */
+    pub fn sample(&self, uv: Point) -> Rgba {
+        let none = Point { x: 0.0, y: 0.0 };
+        self.sample_grad(uv, none, none)
+    }
+
+    /**
+     * The color at `uv` for a pixel whose texture coordinates change by
+     * `dx` to the next pixel on the right and by `dy` to the next one up,
+     * e.g. from `Shader::fragment_with_derivatives`.
+     */
+    pub fn sample_grad(&self, uv: Point, dx: Point, dy: Point) -> Rgba {
+        // the footprint's axes in full size texels.
+        let (width, height) = (self.width() as f32, self.height() as f32);
+        let length = |d: Point| (d.x * width).hypot(d.y * height);
+        let (lx, ly) = (length(dx), length(dy));
+
+        match self.filter {
+            TextureFilter::Nearest => self.nearest(0, uv),
+            TextureFilter::Bilinear => self.bilinear(0, uv),
+            TextureFilter::Trilinear => self.trilinear(uv, lx.max(ly).log2()),
+            TextureFilter::Anisotropic(max_probes) => {
+                let (major, minor, axis) = if lx >= ly { (lx, ly, dx) } else { (ly, lx, dy) };
+                let probes = if major > 0.0 {
+                    ((major / minor).ceil() as usize).clamp(1, max_probes.max(1))
+                } else {
+                    1
+                };
+                let lod = (major / probes as f32).log2();
+
+                let mut sum = Rgba {
+                    red: 0.0,
+                    green: 0.0,
+                    blue: 0.0,
+                    alpha: 0.0,
+                };
+                for k in 0..probes {
+                    let offset = (k as f32 + 0.5) / probes as f32 - 0.5;
+                    let probe = self.trilinear(uv + axis * offset, lod);
+                    sum = sum.lerp(probe, 1.0 / (k + 1) as f32);
+                }
+                sum
+            }
+        }
+    }
+
+    // texel space of a level: the level's texel (i, j) covers [i, i + 1] x
+    // [j, j + 1].
+    fn texel_space(&self, level: usize, uv: Point) -> (f32, f32) {
+        let (width, height) = self.level_size(level);
+        (uv.x * width as f32, (1.0 - uv.y) * height as f32)
+    }
+
+    fn nearest(&self, level: usize, uv: Point) -> Rgba {
+        let (s, t) = self.texel_space(level, uv);
+        self.texel(level, s.floor() as i64, t.floor() as i64)
+    }
+
+    fn bilinear(&self, level: usize, uv: Point) -> Rgba {
+        // relative to the texel centers.
+        let (s, t) = self.texel_space(level, uv);
+        let (s, t) = (s - 0.5, t - 0.5);
+        let (i, j) = (s.floor(), t.floor());
+        let (fs, ft) = (s - i, t - j);
+        let (i, j) = (i as i64, j as i64);
+
+        let top = self
+            .texel(level, i, j)
+            .lerp(self.texel(level, i + 1, j), fs);
+        let bottom = self
+            .texel(level, i, j + 1)
+            .lerp(self.texel(level, i + 1, j + 1), fs);
+        top.lerp(bottom, ft)
+    }
+
+    // `lod` is the log2 of the footprint size in full size texels; levels
+    // past either end of the chain are clamped to it.
+    fn trilinear(&self, uv: Point, lod: f32) -> Rgba {
+        let last = (self.levels.len() - 1) as f32;
+        // also maps NaN, from an empty footprint, to the full size image.
+        let lod = lod.max(0.0).min(last);
+        let level = lod.floor();
+        let blend = lod - level;
+
+        let fine = self.bilinear(level as usize, uv);
+        if blend == 0.0 {
+            fine
+        } else {
+            fine.lerp(self.bilinear(level as usize + 1, uv), blend)
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use generic_array::sequence::{Lengthen, Shorten};
+use generic_array::{ArrayLength, GenericArray};
+use geometry::{Point, Point3D};
+use matrix::Matrix4x4;
+use shader::Varying;
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+use typenum::{Add1, Sub1, B1, U2, U3, U4};
+
+/*
+ * Vectors of any length over f32, stored in a `GenericArray` so that the
+ * length is part of the type: `Vector<U3>` and `Vector<U4>` are different
+ * types and adding one to the other does not compile. `Vec2`, `Vec3` and
+ * `Vec4` name the usual sizes.
+ */
+
+/**
+ * Lengths a `Vector` can have: any typenum unsigned integer, e.g. `U3`.
+ */
+pub trait Dim: ArrayLength<f32, ArrayType: Copy> {}
+
+impl<N: ArrayLength<f32, ArrayType: Copy>> Dim for N {}
+
+pub struct Vector<N: Dim> {
+    data: GenericArray<f32, N>,
+}
+
+pub type Vec2 = Vector<U2>;
+pub type Vec3 = Vector<U3>;
+pub type Vec4 = Vector<U4>;
+
+impl<N: Dim> Clone for Vector<N> {
+    fn clone(&self) -> Vector<N> {
+        *self
+    }
+}
+
+impl<N: Dim> Copy for Vector<N> {}
+
+impl<N: Dim> fmt::Debug for Vector<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Vector{:?}", self.as_slice())
+    }
+}
+
+impl<N: Dim> PartialEq for Vector<N> {
+    fn eq(&self, other: &Vector<N>) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<N: Dim> Default for Vector<N> {
+    fn default() -> Vector<N> {
+        Vector::zero()
+    }
+}
+
+impl<N: Dim> Vector<N> {
+    pub fn from_array(data: GenericArray<f32, N>) -> Vector<N> {
+        Vector { data }
+    }
+
+    /**
+     * The vector holding `values`, or `None` unless there are exactly `N`
+     * of them.
+     */
+    pub fn from_slice(values: &[f32]) -> Option<Vector<N>> {
+        if values.len() != N::to_usize() {
+            return None;
+        }
+        Some(Vector {
+            data: GenericArray::clone_from_slice(values),
+        })
+    }
+
+    pub fn zero() -> Vector<N> {
+        Vector::splat(0.0)
+    }
+
+    // every component set to `value`.
+    pub fn splat(value: f32) -> Vector<N> {
+        Vector::from_fn(|_| value)
+    }
+
+    // the vector whose i-th component is `f(i)`.
+    fn from_fn<F: FnMut(usize) -> f32>(mut f: F) -> Vector<N> {
+        let mut v = Vector {
+            data: GenericArray::default(),
+        };
+        for (i, c) in v.data.iter_mut().enumerate() {
+            *c = f(i);
+        }
+        v
+    }
+
+    // combine the components of two vectors pairwise.
+    fn zip<F: Fn(f32, f32) -> f32>(self, other: Vector<N>, f: F) -> Vector<N> {
+        Vector::from_fn(|i| f(self.data[i], other.data[i]))
+    }
+
+    fn map<F: Fn(f32) -> f32>(self, f: F) -> Vector<N> {
+        Vector::from_fn(|i| f(self.data[i]))
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        self.data.as_slice()
+    }
+
+    pub fn dot(self, other: Vector<N>) -> f32 {
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .map(|(a, b)| a * b)
+            .sum()
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    /**
+     * The unit vector in the same direction. The zero vector has none and
+     * gives NaNs.
+     */
+    pub fn normalized(self) -> Vector<N> {
+        self / self.length()
+    }
+
+    /**
+     * Linear interpolation: `self` at t = 0 and `other` at t = 1.
+     */
+    pub fn lerp(self, other: Vector<N>, t: f32) -> Vector<N> {
+        self + (other - self) * t
+    }
+
+    // componentwise minimum.
+    pub fn min(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, f32::min)
+    }
+
+    // componentwise maximum.
+    pub fn max(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, f32::max)
+    }
+
+    /**
+     * The vector with `w` appended, e.g. a `Vec3` point with w = 1 or
+     * direction with w = 0 as a `Vec4`.
+     */
+    pub fn extend(self, w: f32) -> Vector<Add1<N>>
+    where
+        N: Add<B1>,
+        Add1<N>: Dim + Sub<B1, Output = N>,
+    {
+        Vector {
+            data: self.data.append(w),
+        }
+    }
+
+    /**
+     * The vector without its last component.
+     */
+    pub fn truncate(self) -> Vector<Sub1<N>>
+    where
+        N: Sub<B1>,
+        Sub1<N>: Dim + Add<B1, Output = N>,
+    {
+        Vector {
+            data: self.data.pop_back().0,
+        }
+    }
+
+    /**
+     * Homogeneous coordinates of a point: the vector with w = 1 appended.
+     */
+    pub fn to_homogeneous(self) -> Vector<Add1<N>>
+    where
+        N: Add<B1>,
+        Add1<N>: Dim + Sub<B1, Output = N>,
+    {
+        self.extend(1.0)
+    }
+
+    /**
+     * The point with homogeneous coordinates `self`: all but the last
+     * component, divided by the last one.
+     */
+    pub fn from_homogeneous(self) -> Vector<Sub1<N>>
+    where
+        N: Sub<B1>,
+        Sub1<N>: Dim + Add<B1, Output = N>,
+    {
+        let (point, w) = self.data.pop_back();
+        Vector { data: point } / w
+    }
+}
+
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Vec2 {
+        Vector::from_array(GenericArray::from([x, y]))
+    }
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Vec3 {
+        Vector::from_array(GenericArray::from([x, y, z]))
+    }
+
+    /**
+     * The cross product, perpendicular to both vectors following the right
+     * hand rule.
+     */
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        let (a, b) = (self, other);
+        Vec3::new(
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        )
+    }
+}
+
+impl Vec4 {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Vec4 {
+        Vector::from_array(GenericArray::from([x, y, z, w]))
+    }
+}
+
+impl<N: Dim> Index<usize> for Vector<N> {
+    type Output = f32;
+
+    fn index(&self, i: usize) -> &f32 {
+        &self.data[i]
+    }
+}
+
+impl<N: Dim> IndexMut<usize> for Vector<N> {
+    fn index_mut(&mut self, i: usize) -> &mut f32 {
+        &mut self.data[i]
+    }
+}
+
+impl<N: Dim> Add for Vector<N> {
+    type Output = Vector<N>;
+
+    fn add(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, |a, b| a + b)
+    }
+}
+
+impl<N: Dim> Sub for Vector<N> {
+    type Output = Vector<N>;
+
+    fn sub(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, |a, b| a - b)
+    }
+}
+
+// componentwise product.
+impl<N: Dim> Mul for Vector<N> {
+    type Output = Vector<N>;
+
+    fn mul(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, |a, b| a * b)
+    }
+}
+
+// componentwise quotient.
+impl<N: Dim> Div for Vector<N> {
+    type Output = Vector<N>;
+
+    fn div(self, other: Vector<N>) -> Vector<N> {
+        self.zip(other, |a, b| a / b)
+    }
+}
+
+impl<N: Dim> Neg for Vector<N> {
+    type Output = Vector<N>;
+
+    fn neg(self) -> Vector<N> {
+        self.map(|a| -a)
+    }
+}
+
+// the scalar is added to every component.
+impl<N: Dim> Add<f32> for Vector<N> {
+    type Output = Vector<N>;
+
+    fn add(self, rhs: f32) -> Vector<N> {
+        self.map(|a| a + rhs)
+    }
+}
+
+impl<N: Dim> Sub<f32> for Vector<N> {
+    type Output = Vector<N>;
+
+    fn sub(self, rhs: f32) -> Vector<N> {
+        self.map(|a| a - rhs)
+    }
+}
+
+impl<N: Dim> Mul<f32> for Vector<N> {
+    type Output = Vector<N>;
+
+    fn mul(self, rhs: f32) -> Vector<N> {
+        self.map(|a| a * rhs)
+    }
+}
+
+impl<N: Dim> Mul<Vector<N>> for f32 {
+    type Output = Vector<N>;
+
+    fn mul(self, rhs: Vector<N>) -> Vector<N> {
+        rhs * self
+    }
+}
+
+impl<N: Dim> Div<f32> for Vector<N> {
+    type Output = Vector<N>;
+
+    fn div(self, rhs: f32) -> Vector<N> {
+        self.map(|a| a / rhs)
+    }
+}
+
+impl<N: Dim> AddAssign for Vector<N> {
+    fn add_assign(&mut self, other: Vector<N>) {
+        *self = *self + other;
+    }
+}
+
+impl<N: Dim> SubAssign for Vector<N> {
+    fn sub_assign(&mut self, other: Vector<N>) {
+        *self = *self - other;
+    }
+}
+
+impl<N: Dim> MulAssign<f32> for Vector<N> {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl<N: Dim> DivAssign<f32> for Vector<N> {
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
+    }
+}
+
+impl<N: Dim> Varying for Vector<N> {
+    fn interpolate(values: &[Vector<N>; 3], weights: Point3D) -> Vector<N> {
+        values[0] * weights.x + values[1] * weights.y + values[2] * weights.z
+    }
+}
+
+impl Mul<Vec4> for Matrix4x4 {
+    type Output = Vec4;
+
+    fn mul(self, other: Vec4) -> Vec4 {
+        Vec4::from(self * <[f32; 4]>::from(other))
+    }
+}
+
+impl From<Point> for Vec2 {
+    fn from(p: Point) -> Vec2 {
+        Vec2::new(p.x, p.y)
+    }
+}
+
+impl From<Vec2> for Point {
+    fn from(v: Vec2) -> Point {
+        Point { x: v[0], y: v[1] }
+    }
+}
+
+impl From<Point3D> for Vec3 {
+    fn from(p: Point3D) -> Vec3 {
+        Vec3::new(p.x, p.y, p.z)
+    }
+}
+
+impl From<Vec3> for Point3D {
+    fn from(v: Vec3) -> Point3D {
+        Point3D::new([v[0], v[1], v[2]])
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from(a: [f32; 4]) -> Vec4 {
+        Vector::from_array(GenericArray::from(a))
+    }
+}
+
+impl From<Vec4> for [f32; 4] {
+    fn from(v: Vec4) -> [f32; 4] {
+        [v[0], v[1], v[2], v[3]]
+    }
+}
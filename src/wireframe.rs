@@ -0,0 +1,162 @@
+use camera::Camera;
+use clip;
+use geometry::Point3D;
+use line;
+use matrix::Matrix4x4;
+use mesh::Mesh;
+use shadow::DepthShader;
+use {ImageMeta, Multisample, RGBPixel};
+
+/*
+ * Drawing the edges of a mesh as one pixel lines, each shared edge once.
+ * Edges are clipped against the view volume in clip space like triangles
+ * are, and carry their depth so they can be tested against a z-buffer.
+ *
+ * An edge lies on the faces it bounds, but its pixels are not the samples
+ * the faces were rasterized at, so its depth differs from theirs by up to
+ * about a pixel's worth of the faces' depth slope. As with OpenGL's polygon
+ * offset, an edge is moved towards the viewer by `offset_factor` times the
+ * steepest of those slopes plus a constant `offset_units`. A fixed amount
+ * would not do: depth is nonlinear in distance, so the same amount is a
+ * sliver of a unit near the camera and several units far from it.
+ */
+
+/**
+ * Which edges `ImageMeta::draw_wireframe` draws.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WireframeMode {
+    // every edge, whatever is in front of it.
+    All,
+    // the edges not hidden by anything already in the image's z-buffer,
+    // e.g. to outline a mesh just drawn with `draw_mesh`.
+    Overlay,
+    // the edges not hidden by the mesh itself; the image's own depth is
+    // neither used nor changed.
+    HiddenLine,
+}
+
+pub struct Wireframe {
+    // world to clip space.
+    pub transform: Matrix4x4,
+    pub color: RGBPixel,
+    pub mode: WireframeMode,
+    // multiple of the depth slope, in z-buffer units per pixel, of the
+    // faces along an edge that it is moved towards the viewer.
+    pub offset_factor: f32,
+    // constant part of the offset, in z-buffer units, covering rounding.
+    pub offset_units: f32,
+}
+
+impl Wireframe {
+    pub fn new(camera: &Camera, color: RGBPixel, mode: WireframeMode) -> Wireframe {
+        Wireframe {
+            transform: camera.view_projection_matrix(),
+            color,
+            mode,
+            offset_factor: 2.0,
+            offset_units: 1e-6,
+        }
+    }
+}
+
+impl ImageMeta {
+    /**
+     * Draw the edges of `mesh` chosen by `wireframe.mode`. Only the color
+     * buffer is written.
+     */
+    pub fn draw_wireframe(&mut self, mesh: &Mesh, wireframe: &Wireframe) {
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        let clip: Vec<[f32; 4]> = mesh
+            .positions
+            .iter()
+            .map(|p| wireframe.transform * [p.x, p.y, p.z, 1.0])
+            .collect();
+        let slopes: Vec<f32> = mesh
+            .indices
+            .chunks(3)
+            .map(|face| self.depth_slope([clip[face[0]], clip[face[1]], clip[face[2]]]))
+            .collect();
+
+        // the visible part of each edge in image space, with its depth
+        // offset.
+        let mut segments = vec![];
+        let face_edges = mesh.face_edges();
+        for run in face_edges.chunk_by(|x, y| x.0 == y.0) {
+            let (a, b) = run[0].0;
+            let slope = run
+                .iter()
+                .map(|&(_, face)| slopes[face])
+                .fold(0.0, f32::max);
+            let offset = wireframe.offset_factor * slope + wireframe.offset_units;
+
+            if let Some((a, b)) = clip::clip_line(clip[a], clip[b]) {
+                segments.push((self.viewport(a), self.viewport(b), offset));
+            }
+        }
+
+        let own_depth;
+        let depth = match wireframe.mode {
+            WireframeMode::All => None,
+            WireframeMode::Overlay => Some(&self.zbuffer),
+            WireframeMode::HiddenLine => {
+                own_depth = self.depth_of(mesh, wireframe.transform);
+                Some(&own_depth)
+            }
+        };
+
+        let buffer = &mut self.buffer;
+        for (a, b, offset) in segments {
+            // pixels containing the end points; the view volume ends on the
+            // image's far edges, which belong to no pixel.
+            let pixel = |x: f32, n: i64| (x.floor() as i64).min(n - 1);
+            let start = (pixel(a.x, width), pixel(a.y, height));
+            let end = (pixel(b.x, width), pixel(b.y, height));
+
+            line::bresenham(start, end, |x, y, t| {
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    return;
+                }
+                let idx = ((height - 1 - y) * width + x) as usize;
+                let z = a.z + (b.z - a.z) * t;
+
+                if depth.is_none_or(|depth| z + offset >= depth[idx]) {
+                    buffer[idx] = wireframe.color;
+                }
+            });
+        }
+    }
+
+    // the largest change of z-buffer depth per pixel along x or y across a
+    // face given in clip space, 0 for faces crossing the eye plane or seen
+    // edge on.
+    fn depth_slope(&self, clip: [[f32; 4]; 3]) -> f32 {
+        if clip.iter().any(|v| v[3] <= 0.0) {
+            return 0.0;
+        }
+        let v: Vec<Point3D> = clip.iter().map(|&v| self.viewport(v)).collect();
+        let (e1, e2) = (v[1] - v[0], v[2] - v[0]);
+
+        // z = z0 + dz_dx * x + dz_dy * y through the three vertices.
+        let area = e1.x * e2.y - e1.y * e2.x;
+        if area.abs() < 1e-6 {
+            return 0.0;
+        }
+        let dz_dx = (e1.z * e2.y - e2.z * e1.y) / area;
+        let dz_dy = (e1.x * e2.z - e2.x * e1.z) / area;
+        dz_dx.abs().max(dz_dy.abs())
+    }
+
+    // the z-buffer of `mesh` drawn on its own into an image of this size.
+    fn depth_of(&self, mesh: &Mesh, transform: Matrix4x4) -> Vec<f32> {
+        let mut image = ImageMeta::new(self.width as usize, self.height as usize)
+            .expect("the size of an existing image");
+        image.rasterizer = self.rasterizer;
+        image.threads = self.threads;
+        image.multisample = Multisample::Off;
+
+        image.draw_mesh(mesh, &mut DepthShader { transform });
+        image.zbuffer
+    }
+}
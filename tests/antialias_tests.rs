@@ -0,0 +1,290 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tiny_renderer::shading::GouraudShader;
+use tiny_renderer::{
+    Camera, Error, Filter, ImageMeta, Mesh, Multisample, Point3D, Projection, RGBPixel, Rasterizer,
+    Shader, Vertex,
+};
+
+/*
+ * Draws white in normalized device coordinates and counts its fragments.
+ */
+struct CountingShader {
+    fragments: AtomicUsize,
+}
+
+impl CountingShader {
+    fn new() -> CountingShader {
+        CountingShader {
+            fragments: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Shader for CountingShader {
+    type Varying = ();
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], ()) {
+        let p = vertex.position;
+        ([p[0], p[1], -1e-3 * vertex.face as f32, 1.0], ())
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        self.fragments.fetch_add(1, Ordering::Relaxed);
+        Some(RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        })
+    }
+}
+
+fn mesh(positions: &[[f32; 3]], indices: Vec<usize>) -> Mesh {
+    Mesh::new(
+        positions.iter().map(|&p| Point3D::new(p)).collect(),
+        vec![],
+        vec![],
+        indices,
+    )
+    .unwrap()
+}
+
+// a thin sliver with long edges at awkward slopes.
+fn triangle() -> Mesh {
+    mesh(
+        &[[-0.9, -0.8, 0.0], [0.85, -0.3, 0.0], [-0.2, 0.9, 0.0]],
+        vec![0, 1, 2],
+    )
+}
+
+fn render(mesh: &Mesh, width: usize, height: usize, multisample: Multisample) -> ImageMeta {
+    let mut image = ImageMeta::new(width, height).unwrap();
+    image.multisample = multisample;
+    image.draw_mesh(mesh, &mut CountingShader::new());
+    image
+}
+
+fn partial(image: &ImageMeta) -> usize {
+    image
+        .buffer
+        .iter()
+        .filter(|p| p.red > 0 && p.red < 255)
+        .count()
+}
+
+#[test]
+fn multisampling_blends_edges() {
+    let aliased = render(&triangle(), 64, 64, Multisample::Off);
+    assert_eq!(partial(&aliased), 0);
+
+    let mut previous = 0;
+    for &multisample in [Multisample::X2, Multisample::X4, Multisample::X8].iter() {
+        let image = render(&triangle(), 64, 64, multisample);
+
+        // more samples give more shades along the edges.
+        assert!(partial(&image) > previous, "{:?}", multisample);
+        previous = partial(&image);
+
+        // the covered area stays the same, give or take the edge pixels.
+        let area = |image: &ImageMeta| {
+            image
+                .buffer
+                .iter()
+                .map(|p| p.red as f32 / 255.0)
+                .sum::<f32>()
+        };
+        assert!((area(&image) - area(&aliased)).abs() < 0.02 * area(&aliased));
+    }
+}
+
+#[test]
+fn both_rasterizers_multisample() {
+    for &rasterizer in [Rasterizer::EdgeFunction, Rasterizer::BoundingBox].iter() {
+        let mut image = ImageMeta::new(64, 64).unwrap();
+        image.multisample = Multisample::X4;
+        image.rasterizer = rasterizer;
+        image.draw_mesh(&triangle(), &mut CountingShader::new());
+
+        assert!(partial(&image) > 0, "{:?}", rasterizer);
+        assert_eq!(image.buffer[32 * 64 + 20].red, 255, "{:?}", rasterizer);
+    }
+}
+
+#[test]
+fn fragments_run_once_per_pixel() {
+    let mut shader = CountingShader::new();
+    let mut image = ImageMeta::new(64, 64).unwrap();
+    image.multisample = Multisample::X8;
+    image.draw_mesh(&triangle(), &mut shader);
+
+    let touched = image.buffer.iter().filter(|p| p.red > 0).count();
+    assert_eq!(shader.fragments.load(Ordering::Relaxed), touched);
+}
+
+#[test]
+fn shared_edges_cover_every_sample_once() {
+    // two triangles of a full screen quad, split along a diagonal.
+    let quad = mesh(
+        &[
+            [-1.0, -1.0, 0.0],
+            [1.0, -1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0],
+        ],
+        vec![0, 1, 2, 0, 2, 3],
+    );
+
+    for &threads in [1, 3].iter() {
+        let mut image = ImageMeta::new(37, 29).unwrap();
+        image.multisample = Multisample::X4;
+        image.threads = threads;
+        image.draw_mesh(&quad, &mut CountingShader::new());
+
+        // a gap would leave a black sample and darken its pixel.
+        assert!(image.buffer.iter().all(|p| p.red == 255));
+    }
+}
+
+#[test]
+fn tiled_multisampling_matches_single_thread() {
+    let (meshes, _) = Mesh::load_obj(Path::new("./data/african_head.obj")).unwrap();
+    let camera = Camera::look_at(
+        Point3D::new([0.5, 0.3, 2.5]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: 50.0_f32.to_radians(),
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let mut shader = GouraudShader {
+        transform: camera.view_projection_matrix(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: RGBPixel {
+            red: 200,
+            green: 180,
+            blue: 160,
+        },
+    };
+
+    let mut images = vec![];
+    for &threads in [1, 3].iter() {
+        let mut image = ImageMeta::new(90, 90).unwrap();
+        image.multisample = Multisample::X4;
+        image.threads = threads;
+        image.draw_mesh(&meshes[0], &mut shader);
+        images.push(image);
+    }
+
+    let rgb = |p: &RGBPixel| (p.red, p.green, p.blue);
+    assert!(partial(&images[0]) > 0);
+    assert!(images[0]
+        .buffer
+        .iter()
+        .map(rgb)
+        .eq(images[1].buffer.iter().map(rgb)));
+}
+
+#[test]
+fn drawing_between_meshes_survives_multisampling() {
+    let red = RGBPixel {
+        red: 255,
+        green: 0,
+        blue: 0,
+    };
+    let mut image = ImageMeta::new(32, 32).unwrap();
+    image.multisample = Multisample::X4;
+
+    image.draw_mesh(&triangle(), &mut CountingShader::new());
+    image.draw_line(0, 2, 31, 2, &red);
+    // a second mesh well away from the line.
+    let corner = mesh(
+        &[[0.5, 0.5, 0.0], [0.9, 0.5, 0.0], [0.9, 0.9, 0.0]],
+        vec![0, 1, 2],
+    );
+    image.draw_mesh(&corner, &mut CountingShader::new());
+
+    let row = &image.buffer[29 * 32..30 * 32];
+    assert!(row.iter().all(|&p| p == red));
+    // the mesh was still drawn.
+    assert_eq!(
+        image.buffer[6 * 32 + 28],
+        RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255
+        }
+    );
+}
+
+#[test]
+fn box_filter_averages_blocks() {
+    let mut image = ImageMeta::new(4, 2).unwrap();
+    for (i, pixel) in image.buffer.iter_mut().enumerate() {
+        *pixel = RGBPixel {
+            red: 10 * i as u8,
+            green: 255,
+            blue: 0,
+        };
+        image.zbuffer[i] = i as f32;
+    }
+
+    let small = image.downsample(2, Filter::Box).unwrap();
+
+    assert_eq!((small.width, small.height), (2, 1));
+    // (0 + 10 + 40 + 50) / 4 and (20 + 30 + 60 + 70) / 4
+    assert_eq!(small.buffer[0].red, 25);
+    assert_eq!(small.buffer[1].red, 45);
+    assert_eq!(small.buffer[1].green, 255);
+    assert_eq!(small.zbuffer, vec![5.0, 7.0]);
+}
+
+#[test]
+fn tent_filter_keeps_flat_colors() {
+    let mut image = ImageMeta::new(9, 6).unwrap();
+    for pixel in image.buffer.iter_mut() {
+        *pixel = RGBPixel {
+            red: 200,
+            green: 100,
+            blue: 50,
+        };
+    }
+
+    let small = image.downsample(3, Filter::Tent).unwrap();
+
+    assert_eq!((small.width, small.height), (3, 2));
+    assert!(small
+        .buffer
+        .iter()
+        .all(|p| (p.red, p.green, p.blue) == (200, 100, 50)));
+}
+
+#[test]
+fn supersampling_blends_edges() {
+    let large = render(&triangle(), 256, 256, Multisample::Off);
+
+    for &filter in [Filter::Box, Filter::Tent].iter() {
+        let image = large.downsample(4, filter).unwrap();
+
+        assert_eq!((image.width, image.height), (64, 64));
+        assert!(partial(&image) > 0, "{:?}", filter);
+        assert_eq!(image.buffer[32 * 64 + 20].red, 255, "{:?}", filter);
+        assert_eq!(image.buffer[0].red, 0, "{:?}", filter);
+    }
+}
+
+#[test]
+fn downsampling_to_nothing_is_an_error() {
+    let image = ImageMeta::new(4, 4).unwrap();
+
+    for &factor in [0, 5].iter() {
+        match image.downsample(factor, Filter::Box) {
+            Err(Error::InvalidDimensions { .. }) => (),
+            _ => panic!("expected factor {} to be rejected", factor),
+        }
+    }
+}
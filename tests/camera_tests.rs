@@ -0,0 +1,102 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::{Camera, Point3D, Projection};
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+fn camera(projection: Projection) -> Camera {
+    Camera::look_at(
+        Point3D::new([1.0, 2.0, 5.0]),
+        Point3D::new([1.0, 2.0, 0.0]),
+        Point3D::new([0.0, 1.0, 0.0]),
+        projection,
+    )
+}
+
+#[test]
+fn view_matrix_moves_eye_to_origin() {
+    let cam = camera(Projection::Perspective {
+        fovy: 1.0,
+        aspect: 1.0,
+        near: 1.0,
+        far: 10.0,
+    });
+    let view = cam.view_matrix();
+
+    let eye = view * [1.0, 2.0, 5.0, 1.0];
+    for &coord in eye[..3].iter() {
+        assert_close(coord, 0.0);
+    }
+
+    // the target lies straight ahead, down the negative z-axis.
+    let target = view * [1.0, 2.0, 0.0, 1.0];
+    assert_close(target[0], 0.0);
+    assert_close(target[1], 0.0);
+    assert_close(target[2], -5.0);
+}
+
+#[test]
+fn perspective_maps_near_and_far_planes() {
+    let cam = camera(Projection::Perspective {
+        fovy: 90.0_f32.to_radians(),
+        aspect: 2.0,
+        near: 1.0,
+        far: 10.0,
+    });
+    let projection = cam.projection_matrix();
+
+    let near = projection * [0.0, 0.0, -1.0, 1.0];
+    assert_close(near[2] / near[3], -1.0);
+
+    let far = projection * [0.0, 0.0, -10.0, 1.0];
+    assert_close(far[2] / far[3], 1.0);
+
+    // the top-right corner of the near plane maps to the corner of the cube.
+    let corner = projection * [2.0, 1.0, -1.0, 1.0];
+    assert_close(corner[0] / corner[3], 1.0);
+    assert_close(corner[1] / corner[3], 1.0);
+}
+
+#[test]
+fn orthographic_maps_extents_to_unit_cube() {
+    let cam = camera(Projection::Orthographic {
+        left: -2.0,
+        right: 2.0,
+        bottom: -1.0,
+        top: 1.0,
+        near: 1.0,
+        far: 9.0,
+    });
+    let projection = cam.projection_matrix();
+
+    let corner = projection * [-2.0, 1.0, -1.0, 1.0];
+    assert_close(corner[0], -1.0);
+    assert_close(corner[1], 1.0);
+    assert_close(corner[2], -1.0);
+    assert_close(corner[3], 1.0);
+
+    let far = projection * [0.0, 0.0, -9.0, 1.0];
+    assert_close(far[2], 1.0);
+}
+
+#[test]
+fn viewport_puts_near_plane_in_front() {
+    let viewport = Camera::viewport_matrix(800.0, 600.0);
+
+    let near = viewport * [-1.0, 1.0, -1.0, 1.0];
+    assert_close(near[0], 0.0);
+    assert_close(near[1], 600.0);
+    assert_close(near[2], 1.0);
+
+    let far = viewport * [1.0, -1.0, 1.0, 1.0];
+    assert_close(far[0], 800.0);
+    assert_close(far[1], 0.0);
+    assert_close(far[2], 0.0);
+}
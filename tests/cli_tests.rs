@@ -0,0 +1,232 @@
+extern crate tiny_renderer;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use tiny_renderer::imagefmt::{self, ColFmt};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tiny_renderer"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("tiny_renderer_cli_{}", name))
+}
+
+// render the head in `mode` into a small image and return its pixels.
+fn render(mode: &str, name: &str) -> Vec<u8> {
+    let path = temp_path(name);
+    let output = run(&[
+        "./data/african_head.obj",
+        "--shading",
+        mode,
+        "--width",
+        "64",
+        "--height",
+        "48",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let image = imagefmt::read(&path, ColFmt::RGB).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!((image.w, image.h), (64, 48));
+    image.buf
+}
+
+#[test]
+fn renders_every_untextured_mode() {
+    for mode in ["wireframe", "hidden-line", "flat", "gouraud", "phong"].iter() {
+        let pixels = render(mode, &format!("{}.png", mode));
+
+        assert!(pixels.iter().any(|&c| c > 0), "{} drew nothing", mode);
+    }
+}
+
+#[test]
+fn format_option_overrides_extension() {
+    let path = temp_path("format.img");
+    let output = run(&[
+        "./data/african_head.obj",
+        "-w",
+        "8",
+        "-H",
+        "8",
+        "-f",
+        "ppm",
+        "-o",
+        path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(bytes.starts_with(b"P6\n8 8\n255\n"));
+}
+
+#[test]
+fn antialiasing_keeps_the_image_size() {
+    for (i, args) in [["--msaa", "4"], ["--ssaa", "3"]].iter().enumerate() {
+        let path = temp_path(&format!("antialias{}.png", i));
+        let output = run(&[
+            "./data/african_head.obj",
+            "-w",
+            "24",
+            "-H",
+            "16",
+            args[0],
+            args[1],
+            "-o",
+            path.to_str().unwrap(),
+        ]);
+        assert!(output.status.success(), "{:?}", output);
+
+        let image = imagefmt::read(&path, ColFmt::RGB).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!((image.w, image.h), (24, 16), "{:?}", args);
+        assert!(image.buf.iter().any(|&c| c > 0), "{:?}", args);
+    }
+}
+
+#[test]
+fn bad_arguments_are_usage_errors() {
+    let cases: [&[&str]; 23] = [
+        &[],
+        &["./data/african_head.obj", "--shading", "cel"],
+        &["./data/african_head.obj", "--eye", "1,2"],
+        &["./data/african_head.obj", "--shading", "textured"],
+        &["./data/african_head.obj", "--output", "out.xyz"],
+        &["./data/african_head.obj", "--msaa", "3"],
+        &["./data/african_head.obj", "--ssaa", "0"],
+        &["./data/african_head.obj", "--filter", "cubic"],
+        &[
+            "./data/african_head.obj",
+            "--scene",
+            "./data/pedestal.scene",
+        ],
+        &["./data/african_head.obj", "--shading", "flat", "--shadows"],
+        &[
+            "./data/african_head.obj",
+            "--shading",
+            "gouraud",
+            "--normal-map",
+            "./data/checker.png",
+        ],
+        &["--scene", "./data/pedestal.scene", "-o", "out.png"],
+        &["--scene", "./data/pedestal.scene", "--width", "100"],
+        &[
+            "./data/african_head.obj",
+            "--shading",
+            "flat",
+            "--texture",
+            "./data/checker.png",
+        ],
+        &["./data/african_head.obj", "--filter", "nearest"],
+        &[
+            "./data/african_head.obj",
+            "--shading",
+            "wireframe",
+            "--wireframe",
+        ],
+        &[
+            "./data/african_head.obj",
+            "--shading",
+            "hidden-line",
+            "--msaa",
+            "4",
+        ],
+        &[
+            "./data/african_head.obj",
+            "-s",
+            "flat",
+            "--shading",
+            "gouraud",
+        ],
+        &["./data/african_head.obj", "--fov", "0"],
+        &["./data/african_head.obj", "--fov", "180"],
+        &[
+            "./data/african_head.obj",
+            "--eye",
+            "0,0,3",
+            "--target",
+            "0,0,3",
+        ],
+        &["./data/african_head.obj", "--eye", "0,3,0", "--up", "0,1,0"],
+        &["./data/african_head.obj", "--fov", "-h"],
+    ];
+
+    for args in cases.iter() {
+        let output = run(args);
+
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("usage:"));
+    }
+}
+
+#[test]
+fn short_help_prints_usage() {
+    let output = run(&["-h"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("usage:"));
+}
+
+#[test]
+fn arguments_after_double_dash_are_the_mesh() {
+    let output = run(&["--", "--help"]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("usage:"));
+}
+
+#[test]
+fn render_errors_are_reported() {
+    let output = run(&["./data/does_not_exist.obj", "-o", "out.png"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("OBJ"));
+}
+
+#[test]
+fn scene_files_are_rendered() {
+    let dir = temp_path("scene");
+    fs::create_dir_all(&dir).unwrap();
+    let scene = dir.join("small.scene");
+    fs::write(
+        &scene,
+        format!(
+            "[output]\npath small.png\nsize 32 24\n\n[node head]\nmesh {}\n",
+            env::current_dir()
+                .unwrap()
+                .join("data/african_head.obj")
+                .display()
+        ),
+    )
+    .unwrap();
+
+    let output = run(&["--scene", scene.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let image = imagefmt::read(dir.join("small.png"), ColFmt::RGB).unwrap();
+    assert_eq!((image.w, image.h), (32, 24));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn scene_file_errors_name_the_line() {
+    let path = temp_path("broken.scene");
+    fs::write(&path, "[camera]\neye 0 0 3\nfov wide\n").unwrap();
+
+    let output = run(&["--scene", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("broken.scene: line 3: fov"), "{}", stderr);
+}
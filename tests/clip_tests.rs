@@ -0,0 +1,115 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::clip::{clip_line, clip_triangle, is_inside, triangulate};
+use tiny_renderer::{ImageMeta, Mesh, Point3D, RGBPixel, Shader, Vertex};
+
+#[test]
+fn inside_triangle_is_untouched() {
+    let triangle = [
+        ([-0.5, -0.5, 0.0, 1.0], 0.0),
+        ([0.5, -0.5, 0.0, 1.0], 1.0),
+        ([0.0, 0.5, 0.0, 1.0], 2.0),
+    ];
+
+    let polygon = clip_triangle(&triangle);
+
+    assert_eq!(polygon.len(), 3);
+    assert_eq!(triangulate(&polygon).len(), 1);
+}
+
+#[test]
+fn outside_triangle_is_dropped() {
+    let triangle = [
+        ([2.0, 0.0, 0.0, 1.0], 0.0),
+        ([3.0, 0.0, 0.0, 1.0], 0.0),
+        ([2.5, 0.5, 0.0, 1.0], 0.0),
+    ];
+
+    assert!(clip_triangle(&triangle).is_empty());
+}
+
+#[test]
+fn lines_are_cut_at_the_frustum() {
+    let inside = ([-0.5, 0.0, 0.0, 1.0], [0.5, 0.2, 0.0, 1.0]);
+    assert_eq!(clip_line(inside.0, inside.1), Some(inside));
+
+    // leaves through the right plane, x = w, halfway along.
+    let (a, b) = clip_line([0.0, 0.0, 0.0, 1.0], [2.0, 1.0, 0.0, 1.0]).unwrap();
+    assert_eq!(a, [0.0, 0.0, 0.0, 1.0]);
+    assert_eq!(b, [1.0, 0.5, 0.0, 1.0]);
+
+    // crosses the view volume without an end point inside it.
+    let (a, b) = clip_line([-3.0, 0.0, 0.0, 1.0], [3.0, 0.0, 0.0, 1.0]).unwrap();
+    assert_eq!((a[0], b[0]), (-1.0, 1.0));
+
+    assert_eq!(clip_line([2.0, 0.0, 0.0, 1.0], [3.0, 0.5, 0.0, 1.0]), None);
+    // passes by a corner.
+    assert_eq!(clip_line([0.0, 3.0, 0.0, 1.0], [3.0, 0.0, 0.0, 1.0]), None);
+    // behind the camera.
+    assert_eq!(
+        clip_line([0.0, 0.0, 0.5, -1.0], [0.1, 0.0, 0.5, -2.0]),
+        None
+    );
+}
+
+#[test]
+fn triangle_crossing_near_plane_becomes_quad() {
+    // one vertex behind the camera (w < 0).
+    let triangle = [
+        ([0.0, 0.0, 0.5, 1.0], 0.0),
+        ([0.2, 0.0, 0.5, 1.0], 1.0),
+        ([0.0, 0.0, 1.0, -1.0], 2.0),
+    ];
+
+    let polygon = clip_triangle(&triangle);
+
+    assert_eq!(polygon.len(), 4);
+    assert_eq!(triangulate(&polygon).len(), 2);
+
+    for &(position, varying) in polygon.iter() {
+        assert!(is_inside(&position) || (position[3] + position[2]).abs() < 1e-6);
+        assert!((0.0..=2.0).contains(&varying));
+    }
+}
+
+struct PassThrough;
+
+impl Shader for PassThrough {
+    type Varying = ();
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], ()) {
+        // use z as w, as a perspective projection would.
+        let p = vertex.position;
+        ([p[0], p[1], 0.0, p[2]], ())
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        Some(RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        })
+    }
+}
+
+#[test]
+fn geometry_behind_the_camera_is_not_drawn() {
+    // with w = -1 everywhere a plain perspective divide would mirror the
+    // triangle onto the screen.
+    let mesh = Mesh::new(
+        vec![
+            Point3D::new([-0.5, -0.5, -1.0]),
+            Point3D::new([0.5, -0.5, -1.0]),
+            Point3D::new([0.0, 0.5, -1.0]),
+        ],
+        vec![],
+        vec![],
+        vec![0, 1, 2],
+    )
+    .unwrap();
+
+    let mut image = ImageMeta::new(10, 10).unwrap();
+    image.draw_mesh(&mesh, &mut PassThrough);
+
+    assert!(image.buffer.iter().all(|p| p.red == 0));
+}
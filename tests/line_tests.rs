@@ -0,0 +1,269 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::{Cap, ImageMeta, Join, Point, RGBPixel, Stroke};
+
+const WHITE: RGBPixel = RGBPixel {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+
+// the red channel of pixel (x, y), counting y from the bottom.
+fn red(image: &ImageMeta, x: usize, y: usize) -> u8 {
+    image.buffer[(image.height as usize - 1 - y) * image.width as usize + x].red
+}
+
+fn lit(image: &ImageMeta) -> Vec<(usize, usize)> {
+    let mut pixels = vec![];
+    for y in 0..image.height as usize {
+        for x in 0..image.width as usize {
+            if red(image, x, y) > 0 {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+fn partial(image: &ImageMeta) -> usize {
+    image
+        .buffer
+        .iter()
+        .filter(|p| p.red > 0 && p.red < 255)
+        .count()
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+
+fn hard(width: f32, cap: Cap, join: Join) -> Stroke {
+    Stroke {
+        width,
+        cap,
+        join,
+        antialiased: false,
+    }
+}
+
+#[test]
+fn bresenham_draws_one_pixel_per_step_in_every_octant() {
+    let ends = [
+        (7, 3),
+        (3, 7),
+        (-3, 7),
+        (-7, 3),
+        (-7, -3),
+        (-3, -7),
+        (3, -7),
+        (7, -3),
+    ];
+
+    for &(dx, dy) in ends.iter() {
+        let mut image = ImageMeta::new(20, 20).unwrap();
+        image.draw_line(10, 10, 10 + dx, 10 + dy, &WHITE);
+        let pixels = lit(&image);
+
+        assert_eq!(pixels.len(), 8, "({}, {})", dx, dy);
+        assert!(pixels.contains(&(10, 10)));
+        assert!(pixels.contains(&((10 + dx) as usize, (10 + dy) as usize)));
+
+        // no gaps: consecutive pixels along the major axis touch.
+        let major = |&(x, y): &(usize, usize)| if dx.abs() > dy.abs() { x } else { y };
+        let mut steps: Vec<usize> = pixels.iter().map(major).collect();
+        steps.sort();
+        steps.dedup();
+        assert_eq!(steps.len(), 8, "({}, {})", dx, dy);
+    }
+}
+
+#[test]
+fn bresenham_is_exact_on_diagonals() {
+    let mut image = ImageMeta::new(8, 8).unwrap();
+    image.draw_line(0, 0, 5, 5, &WHITE);
+
+    assert_eq!(lit(&image), (0..6).map(|i| (i, i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn pixels_off_the_image_are_skipped() {
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_line(-5, 3, 25, 3, &WHITE);
+    image.draw_line(-30, -30, -10, 40, &WHITE);
+    image.draw_lines_segment(-8.0, 50.0, 30.0, 60.0, &WHITE);
+
+    assert_eq!(lit(&image), (0..20).map(|x| (x, 3)).collect::<Vec<_>>());
+}
+
+#[test]
+fn far_end_points_are_clipped() {
+    // stepping to the far end pixel by pixel would take a billion steps.
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_line(2, 1, 1_000_000_002, 500_000_001, &WHITE);
+    image.draw_line(-1_000_000_000, 15, 1_000_000_000, 15, &WHITE);
+
+    let mut expected = ImageMeta::new(20, 20).unwrap();
+    expected.draw_line(2, 1, 38, 19, &WHITE);
+    expected.draw_line(0, 15, 19, 15, &WHITE);
+    assert_eq!(lit(&image), lit(&expected));
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_line_aa(-1e9, 10.5, 1e9, 10.5, &WHITE);
+    assert_eq!(lit(&image), (0..20).map(|x| (x, 10)).collect::<Vec<_>>());
+}
+
+#[test]
+fn wu_lines_split_coverage_between_rows() {
+    // through the pixel centers of row 5.
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_line_aa(2.5, 5.5, 15.5, 5.5, &WHITE);
+    assert!((3..15).all(|x| red(&image, x, 5) == 255));
+    assert!((0..20).all(|x| red(&image, x, 4) == 0 && red(&image, x, 6) == 0));
+
+    // halfway between rows 4 and 5.
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_line_aa(2.5, 5.0, 15.5, 5.0, &WHITE);
+    assert!((3..15).all(|x| red(&image, x, 4) == 128 && red(&image, x, 5) == 128));
+}
+
+#[test]
+fn wu_lines_blend_with_the_background() {
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    for pixel in image.buffer.iter_mut() {
+        *pixel = RGBPixel {
+            red: 100,
+            green: 100,
+            blue: 100,
+        };
+    }
+    image.draw_line_aa(2.5, 5.0, 15.5, 5.0, &WHITE);
+
+    assert_eq!(red(&image, 8, 5), 178);
+    assert_eq!(red(&image, 8, 7), 100);
+}
+
+#[test]
+fn wu_lines_are_smooth_on_slopes() {
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    image.draw_line_aa(3.0, 2.0, 37.0, 21.0, &WHITE);
+    image.draw_line_aa(20.0, 1.0, 28.0, 38.0, &WHITE);
+
+    assert!(partial(&image) > 40);
+}
+
+#[test]
+fn stroke_width_and_caps() {
+    let count = |cap: Cap| {
+        let mut image = ImageMeta::new(20, 20).unwrap();
+        let line = [point(2.0, 10.0), point(18.0, 10.0)];
+        image.draw_polyline(&line, &hard(4.0, cap, Join::Miter), &WHITE);
+        lit(&image).len()
+    };
+
+    // 16 columns of 4 rows, plus half the width at each end.
+    assert_eq!(count(Cap::Butt), 64);
+    assert_eq!(count(Cap::Square), 80);
+    assert!(count(Cap::Round) > 64 && count(Cap::Round) < 80);
+}
+
+#[test]
+fn joins_fill_the_outer_corner() {
+    let corner = |join: Join| {
+        let mut image = ImageMeta::new(20, 20).unwrap();
+        let path = [point(5.0, 5.0), point(14.0, 5.0), point(14.0, 14.0)];
+        image.draw_polyline(&path, &hard(4.0, Cap::Butt, join), &WHITE);
+        image
+    };
+    let (miter, round, bevel) = (
+        corner(Join::Miter),
+        corner(Join::Round),
+        corner(Join::Bevel),
+    );
+
+    // the far corner of the miter square.
+    assert_eq!(red(&miter, 15, 3), 255);
+    assert_eq!(red(&round, 15, 3), 0);
+    assert_eq!(red(&bevel, 15, 3), 0);
+
+    // with anti-aliasing the corners cover decreasing areas.
+    let area = |join: Join| {
+        let mut image = ImageMeta::new(20, 20).unwrap();
+        let path = [point(5.0, 5.0), point(14.0, 5.0), point(14.0, 14.0)];
+        let stroke = Stroke {
+            join,
+            ..Stroke::new(4.0)
+        };
+        image.draw_polyline(&path, &stroke, &WHITE);
+        image.buffer.iter().map(|p| p.red as u32).sum::<u32>()
+    };
+    assert!(area(Join::Miter) > area(Join::Round));
+    assert!(area(Join::Round) > area(Join::Bevel));
+}
+
+#[test]
+fn sharp_miters_are_beveled() {
+    let mut image = ImageMeta::new(40, 20).unwrap();
+    let path = [point(2.0, 9.0), point(20.0, 10.0), point(2.0, 11.0)];
+    image.draw_polyline(&path, &hard(2.0, Cap::Butt, Join::Miter), &WHITE);
+
+    assert!(!lit(&image).is_empty());
+    assert!(lit(&image).iter().all(|&(x, _)| x < 22));
+}
+
+#[test]
+fn antialiased_strokes_blend_their_edges() {
+    let path = [point(3.0, 4.0), point(17.0, 9.0), point(6.0, 16.0)];
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_polyline(&path, &Stroke::new(3.0), &WHITE);
+    assert!(partial(&image) > 10);
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_polyline(&path, &hard(3.0, Cap::Butt, Join::Miter), &WHITE);
+    assert_eq!(partial(&image), 0);
+}
+
+#[test]
+fn overlapping_pieces_blend_once() {
+    let stroke = Stroke {
+        cap: Cap::Round,
+        ..Stroke::new(2.5)
+    };
+
+    let mut whole = ImageMeta::new(20, 20).unwrap();
+    whole.draw_polyline(&[point(2.0, 9.7), point(18.0, 9.7)], &stroke, &WHITE);
+
+    // the same line in two pieces, joined in the middle.
+    let mut split = ImageMeta::new(20, 20).unwrap();
+    let path = [point(2.0, 9.7), point(9.3, 9.7), point(18.0, 9.7)];
+    split.draw_polyline(&path, &stroke, &WHITE);
+
+    assert!(whole
+        .buffer
+        .iter()
+        .zip(split.buffer.iter())
+        .all(|(a, b)| a.red == b.red));
+}
+
+#[test]
+fn polygons_are_closed() {
+    let square = [
+        point(5.0, 5.0),
+        point(15.0, 5.0),
+        point(15.0, 15.0),
+        point(5.0, 15.0),
+    ];
+    let stroke = hard(2.0, Cap::Butt, Join::Miter);
+
+    let mut open = ImageMeta::new(20, 20).unwrap();
+    open.draw_polyline(&square, &stroke, &WHITE);
+    let mut closed = ImageMeta::new(20, 20).unwrap();
+    closed.draw_polygon(&square, &stroke, &WHITE);
+
+    // the closing edge and the corners on either side of it.
+    assert_eq!(red(&open, 4, 4), 0);
+    assert_eq!(red(&open, 4, 10), 0);
+    assert_eq!(red(&closed, 4, 4), 255);
+    assert_eq!(red(&closed, 4, 10), 255);
+    assert_eq!(red(&closed, 4, 15), 255);
+}
@@ -1,7 +1,31 @@
 extern crate tiny_renderer;
 
+use std::f32::consts::PI;
 use tiny_renderer::geometry::Point3D;
-use tiny_renderer::matrix::Matrix3x3;
+use tiny_renderer::matrix::{Matrix3x3, Matrix4x4};
+use tiny_renderer::Error;
+
+fn assert_close(a: &Matrix4x4, b: &Matrix4x4) {
+    for (x, y) in a.buffer.iter().zip(b.buffer.iter()) {
+        assert!((x - y).abs() < 1e-5, "\n{:?}!=\n{:?}", a, b);
+    }
+}
+
+fn transform(mat: &Matrix4x4, p: Point3D, w: f32) -> Point3D {
+    let v = mat * [p.x, p.y, p.z, w];
+    Point3D::new([v[0], v[1], v[2]])
+}
+
+fn assert_point_close(a: Point3D, b: Point3D) {
+    assert!((a - b).length() < 1e-5, "{:?} != {:?}", a, b);
+}
+
+// an arbitrary affine transform with every kind of part.
+fn affine() -> Matrix4x4 {
+    Matrix4x4::translation(Point3D::new([1.0, -2.0, 3.0]))
+        * Matrix4x4::rotation(Point3D::new([1.0, 2.0, 0.5]), 0.7)
+        * Matrix4x4::scale(Point3D::new([2.0, 0.5, 3.0]))
+}
 
 #[test]
 fn matrix_creation() {
@@ -71,3 +95,143 @@ fn right_point3d_multiply() {
 
     assert_eq!(result, expected);
 }
+
+#[test]
+fn matrix3x3_multiply() {
+    // a quarter turn about z, twice.
+    let quarter = Matrix3x3 {
+        buffer: [0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+    };
+    let half = quarter * quarter;
+
+    assert_eq!(
+        half * Point3D::new([1.0, 2.0, 3.0]),
+        Point3D::new([-1.0, -2.0, 3.0])
+    );
+    assert_eq!(quarter * Matrix3x3::identity(), quarter);
+}
+
+#[test]
+fn matrix_conversions_keep_the_linear_part() {
+    let mat = affine();
+    let linear = Matrix3x3::from(mat);
+    let p = Point3D::new([0.3, -1.0, 2.0]);
+
+    // directions ignore the translation either way.
+    assert_point_close(linear * p, transform(&mat, p, 0.0));
+
+    let back = Matrix4x4::from(linear);
+    assert_point_close(transform(&back, p, 1.0), linear * p);
+    assert_eq!(Matrix3x3::from(back), linear);
+}
+
+#[test]
+fn transpose_swaps_rows_and_columns() {
+    let mat = affine();
+    let transposed = mat.transpose();
+
+    for i in 0..4 {
+        for j in 0..4 {
+            assert_eq!(transposed.get(i, j), mat.get(j, i));
+        }
+    }
+    assert_eq!(transposed.transpose(), mat);
+}
+
+#[test]
+fn determinants_measure_volume() {
+    assert_eq!(Matrix4x4::identity().determinant(), 1.0);
+    assert_eq!(
+        Matrix4x4::scale(Point3D::new([2.0, 3.0, 4.0])).determinant(),
+        24.0
+    );
+    assert_eq!(
+        Matrix4x4::translation(Point3D::new([5.0, 6.0, 7.0])).determinant(),
+        1.0
+    );
+
+    let rotation = Matrix4x4::rotation(Point3D::new([0.2, -1.0, 0.4]), 1.3);
+    assert!((rotation.determinant() - 1.0).abs() < 1e-5);
+
+    // mirroring flips the sign.
+    let mirror = Matrix4x4::scale(Point3D::new([1.0, -1.0, 1.0]));
+    assert_eq!(mirror.determinant(), -1.0);
+    assert!((affine().determinant() - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn inverse_undoes_the_transform() {
+    let mat = affine();
+    let inverse = mat.inverse().unwrap();
+
+    assert_close(&(mat * inverse), &Matrix4x4::identity());
+    assert_close(&(inverse * mat), &Matrix4x4::identity());
+
+    let offset = Point3D::new([1.0, 2.0, 3.0]);
+    assert_close(
+        &Matrix4x4::translation(offset).inverse().unwrap(),
+        &Matrix4x4::translation(-1.0 * offset),
+    );
+}
+
+#[test]
+fn singular_matrices_have_no_inverse() {
+    let flat = Matrix4x4::scale(Point3D::new([1.0, 0.0, 1.0]));
+    let mut repeated = affine();
+    for j in 0..4 {
+        repeated.set(2, j, repeated.get(1, j));
+    }
+
+    for mat in [flat, repeated, Matrix4x4::zero()].iter() {
+        match mat.inverse() {
+            Err(Error::SingularMatrix) => (),
+            other => panic!("expected a singular matrix, got {:?}", other),
+        }
+    }
+    assert!(flat.inverse_transpose().is_err());
+}
+
+#[test]
+fn inverse_transpose_keeps_normals_perpendicular() {
+    // a surface along (1, 1, 0) with normal (1, -1, 0), stretched along y.
+    let mat = Matrix4x4::scale(Point3D::new([1.0, 2.0, 1.0])) * affine();
+    let tangent = Point3D::new([1.0, 1.0, 0.0]);
+    let normal = Point3D::new([1.0, -1.0, 0.0]);
+
+    let normals = mat.inverse_transpose().unwrap();
+    let tangent = transform(&mat, tangent, 0.0);
+    assert!(transform(&normals, normal, 0.0).dot(tangent).abs() < 1e-5);
+    // the matrix itself would not do.
+    assert!(transform(&mat, normal, 0.0).dot(tangent).abs() > 0.1);
+
+    // rotations are their own inverse transpose.
+    let rotation = Matrix4x4::rotation(Point3D::new([3.0, 1.0, -2.0]), 2.0);
+    assert_close(&rotation.inverse_transpose().unwrap(), &rotation);
+}
+
+#[test]
+fn rotations_follow_the_right_hand_rule() {
+    let x = Point3D::new([1.0, 0.0, 0.0]);
+    let y = Point3D::new([0.0, 1.0, 0.0]);
+    let z = Point3D::new([0.0, 0.0, 1.0]);
+
+    let quarter = Matrix4x4::rotation(z, PI / 2.0);
+    assert_point_close(transform(&quarter, x, 1.0), y);
+    assert_point_close(transform(&quarter, y, 1.0), -1.0 * x);
+
+    // a third of a turn about the diagonal cycles the axes; its length
+    // does not matter.
+    let third = Matrix4x4::rotation(Point3D::new([2.0, 2.0, 2.0]), 2.0 * PI / 3.0);
+    assert_point_close(transform(&third, x, 1.0), y);
+    assert_point_close(transform(&third, y, 1.0), z);
+    assert_point_close(transform(&third, z, 1.0), x);
+}
+
+#[test]
+fn translations_move_points_but_not_directions() {
+    let mat = Matrix4x4::translation(Point3D::new([1.0, 2.0, 3.0]));
+    let p = Point3D::new([1.0, 1.0, 1.0]);
+
+    assert_eq!(transform(&mat, p, 1.0), Point3D::new([2.0, 3.0, 4.0]));
+    assert_eq!(transform(&mat, p, 0.0), p);
+}
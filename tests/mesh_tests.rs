@@ -0,0 +1,179 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use tiny_renderer::{Error, Mesh, MeshError, Point, Point3D};
+
+#[test]
+fn load_african_head() {
+    let (meshes, _) = Mesh::load_obj(Path::new("./data/african_head.obj")).unwrap();
+
+    assert!(!meshes.is_empty());
+
+    let mesh = &meshes[0];
+    assert!(mesh.num_faces() > 0);
+    assert_eq!(mesh.faces().count(), mesh.num_faces());
+    assert_eq!(mesh.texcoords.len(), mesh.positions.len());
+
+    for face in mesh.faces() {
+        assert!(face.texcoords.is_some());
+        for &idx in face.indices.iter() {
+            assert!(idx < mesh.positions.len());
+        }
+    }
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    match Mesh::load_obj(Path::new("./data/does_not_exist.obj")) {
+        Err(Error::Obj(_)) => (),
+        other => panic!("expected a load error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn out_of_range_index_is_an_error() {
+    let positions = vec![Point3D::zero(), Point3D::new([1.0, 0.0, 0.0])];
+
+    match Mesh::new(positions, vec![], vec![], vec![0, 1, 2]) {
+        Err(Error::Mesh(MeshError::IndexOutOfRange { face: 0, index: 2 })) => (),
+        other => panic!("expected an index error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn attribute_count_must_match_positions() {
+    let positions = vec![Point3D::zero(); 3];
+    let texcoords = vec![Point { x: 0.0, y: 0.0 }; 2];
+
+    assert!(Mesh::new(positions, vec![], texcoords, vec![0, 1, 2]).is_err());
+}
+
+#[test]
+fn face_normal_follows_winding() {
+    let positions = vec![
+        Point3D::zero(),
+        Point3D::new([1.0, 0.0, 0.0]),
+        Point3D::new([0.0, 1.0, 0.0]),
+    ];
+    let mesh = Mesh::new(positions, vec![], vec![], vec![0, 1, 2]).unwrap();
+
+    assert_eq!(mesh.face(0).normal(), Point3D::new([0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn computed_normals_are_area_weighted() {
+    // two faces meeting at vertex 0: a large one in the xy-plane and a
+    // small one in the xz-plane.
+    let positions = vec![
+        Point3D::zero(),
+        Point3D::new([4.0, 0.0, 0.0]),
+        Point3D::new([0.0, 4.0, 0.0]),
+        Point3D::new([0.0, 0.0, -1.0]),
+    ];
+    let mesh = Mesh::new(positions, vec![], vec![], vec![0, 1, 2, 0, 1, 3]).unwrap();
+
+    let normal = mesh.normals[0];
+    assert!((normal.length() - 1.0).abs() < 1e-6);
+
+    // the larger face dominates: mostly +z, tilted towards +y.
+    assert!(normal.z > normal.y && normal.y > 0.0);
+    assert!((normal.z / normal.y - 4.0).abs() < 1e-4);
+
+    // vertex 2 only belongs to the first face.
+    assert_eq!(mesh.normals[2], Point3D::new([0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn obj_normals_are_kept() {
+    let (meshes, _) = Mesh::load_obj(Path::new("./data/african_head.obj")).unwrap();
+
+    assert_eq!(meshes[0].normals.len(), meshes[0].positions.len());
+}
+
+fn textured_square(texcoords: Vec<Point>) -> Mesh {
+    let positions = vec![
+        Point3D::new([-1.0, -1.0, 0.0]),
+        Point3D::new([1.0, -1.0, 0.0]),
+        Point3D::new([1.0, 1.0, 0.0]),
+        Point3D::new([-1.0, 1.0, 0.0]),
+    ];
+    Mesh::new(positions, vec![], texcoords, vec![0, 1, 2, 0, 2, 3]).unwrap()
+}
+
+#[test]
+fn tangents_follow_texture_directions() {
+    let mesh = textured_square(vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 1.0, y: 0.0 },
+        Point { x: 1.0, y: 1.0 },
+        Point { x: 0.0, y: 1.0 },
+    ]);
+
+    for idx in 0..4 {
+        assert_eq!(mesh.tangents[idx], Point3D::new([1.0, 0.0, 0.0]));
+        assert_eq!(mesh.bitangents[idx], Point3D::new([0.0, 1.0, 0.0]));
+    }
+}
+
+#[test]
+fn mirrored_texture_flips_bitangent() {
+    // v runs downwards across the square.
+    let mesh = textured_square(vec![
+        Point { x: 0.0, y: 1.0 },
+        Point { x: 1.0, y: 1.0 },
+        Point { x: 1.0, y: 0.0 },
+        Point { x: 0.0, y: 0.0 },
+    ]);
+
+    for idx in 0..4 {
+        assert_eq!(mesh.tangents[idx], Point3D::new([1.0, 0.0, 0.0]));
+        assert_eq!(mesh.bitangents[idx], Point3D::new([0.0, -1.0, 0.0]));
+    }
+}
+
+#[test]
+fn no_tangents_without_texcoords() {
+    let mesh = Mesh::new(vec![Point3D::zero(); 3], vec![], vec![], vec![0, 1, 2]).unwrap();
+
+    assert!(mesh.tangents.is_empty());
+    assert!(mesh.face(0).tangents.is_none());
+}
+
+#[test]
+fn texturing_requires_texcoords() {
+    let mesh = Mesh::new(vec![Point3D::zero(); 3], vec![], vec![], vec![0, 1, 2]).unwrap();
+
+    match mesh.require_texcoords() {
+        Err(Error::Mesh(MeshError::MissingAttribute("texcoords"))) => (),
+        other => panic!("expected a missing attribute error, got {:?}", other),
+    }
+}
+
+#[test]
+fn shared_edges_are_listed_once() {
+    let square = |indices: Vec<usize>, positions: Vec<[f32; 3]>| {
+        let positions = positions.into_iter().map(Point3D::new).collect();
+        Mesh::new(positions, vec![], vec![], indices).unwrap()
+    };
+    let corners = vec![
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+    let indexed = square(vec![0, 1, 2, 0, 2, 3], corners.clone());
+
+    assert_eq!(
+        indexed.edges(),
+        vec![(0, 1), (0, 2), (0, 3), (1, 2), (2, 3)]
+    );
+
+    // the same square with the diagonal's vertices repeated, as along a
+    // texture seam.
+    let mut split = corners.clone();
+    split.push(corners[0]);
+    split.push(corners[2]);
+    let split = square(vec![0, 1, 2, 4, 5, 3], split);
+
+    assert_eq!(split.edges(), indexed.edges());
+}
@@ -0,0 +1,115 @@
+extern crate tiny_renderer;
+
+use std::f32::consts::PI;
+use tiny_renderer::geometry::Point3D;
+use tiny_renderer::matrix::{Matrix4x4, Quaternion};
+
+fn point(x: f32, y: f32, z: f32) -> Point3D {
+    Point3D::new([x, y, z])
+}
+
+fn assert_point_close(a: Point3D, b: Point3D) {
+    assert!((a - b).length() < 1e-5, "{:?} != {:?}", a, b);
+}
+
+// the same rotation, whichever of q and -q each one is.
+fn assert_same_rotation(a: Quaternion, b: Quaternion) {
+    assert!((a.dot(b).abs() - 1.0).abs() < 1e-5, "{:?} != {:?}", a, b);
+}
+
+fn transform(mat: &Matrix4x4, p: Point3D) -> Point3D {
+    let v = mat * [p.x, p.y, p.z, 1.0];
+    point(v[0], v[1], v[2])
+}
+
+#[test]
+fn axis_angle_matches_the_rotation_matrix() {
+    let axis = point(0.3, -1.0, 2.0);
+    let q = Quaternion::from_axis_angle(axis, 1.1);
+    let mat = Matrix4x4::rotation(axis, 1.1);
+
+    for &p in [point(1.0, 0.0, 0.0), point(0.5, 2.0, -3.0)].iter() {
+        assert_point_close(q.rotate(p), transform(&mat, p));
+        assert_point_close(transform(&q.to_matrix(), p), transform(&mat, p));
+        assert_point_close(transform(&Matrix4x4::from(q), p), q.rotate(p));
+    }
+    assert!((q.length() - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn composition_applies_the_right_factor_first() {
+    let quarter_z = Quaternion::from_axis_angle(point(0.0, 0.0, 1.0), PI / 2.0);
+    let quarter_x = Quaternion::from_axis_angle(point(1.0, 0.0, 0.0), PI / 2.0);
+
+    // x turns to y about z, then y to z about x.
+    assert_point_close(
+        (quarter_x * quarter_z).rotate(point(1.0, 0.0, 0.0)),
+        point(0.0, 0.0, 1.0),
+    );
+    // the other way round x stays put, then turns to y.
+    assert_point_close(
+        (quarter_z * quarter_x).rotate(point(1.0, 0.0, 0.0)),
+        point(0.0, 1.0, 0.0),
+    );
+
+    // like the matrices.
+    let p = point(0.2, 0.4, 0.8);
+    let mat = quarter_x.to_matrix() * quarter_z.to_matrix();
+    assert_point_close((quarter_x * quarter_z).rotate(p), transform(&mat, p));
+
+    // a rotation and its conjugate cancel.
+    assert_same_rotation(quarter_x * quarter_x.conjugate(), Quaternion::identity());
+}
+
+#[test]
+fn euler_angles_apply_x_then_y_then_z() {
+    let (x, y, z) = (0.3, -0.8, 1.9);
+    let expected = Quaternion::from_axis_angle(point(0.0, 0.0, 1.0), z)
+        * Quaternion::from_axis_angle(point(0.0, 1.0, 0.0), y)
+        * Quaternion::from_axis_angle(point(1.0, 0.0, 0.0), x);
+
+    assert_same_rotation(Quaternion::from_euler(x, y, z), expected);
+    assert_same_rotation(
+        Quaternion::from_euler(0.0, 0.0, 0.0),
+        Quaternion::identity(),
+    );
+
+    // a quarter turn about y takes z to x.
+    let q = Quaternion::from_euler(0.0, PI / 2.0, 0.0);
+    assert_point_close(q.rotate(point(0.0, 0.0, 1.0)), point(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn slerp_turns_at_a_constant_rate() {
+    let axis = point(1.0, 1.0, 0.0);
+    let a = Quaternion::from_axis_angle(axis, 0.2);
+    let b = Quaternion::from_axis_angle(axis, 1.8);
+
+    assert_same_rotation(a.slerp(b, 0.0), a);
+    assert_same_rotation(a.slerp(b, 1.0), b);
+    for &t in [0.25, 0.5, 0.9].iter() {
+        let expected = Quaternion::from_axis_angle(axis, 0.2 + 1.6 * t);
+        assert_same_rotation(a.slerp(b, t), expected);
+        assert!((a.slerp(b, t).length() - 1.0).abs() < 1e-5);
+    }
+
+    // nlerp agrees halfway, by symmetry, but not a quarter of the way.
+    assert_same_rotation(a.nlerp(b, 0.5), a.slerp(b, 0.5));
+    let quarter = Quaternion::from_axis_angle(axis, 0.6);
+    assert!(a.nlerp(b, 0.25).dot(quarter).abs() < a.slerp(b, 0.25).dot(quarter).abs());
+}
+
+#[test]
+fn interpolation_takes_the_short_way() {
+    let z = point(0.0, 0.0, 1.0);
+    // 350 degrees is 10 degrees the other way.
+    let a = Quaternion::identity();
+    let b = Quaternion::from_axis_angle(z, 350.0_f32.to_radians());
+    let halfway = Quaternion::from_axis_angle(z, -5.0_f32.to_radians());
+
+    assert_same_rotation(a.slerp(b, 0.5), halfway);
+    assert_same_rotation(a.nlerp(b, 0.5), halfway);
+    // and nearly equal rotations do not divide by zero.
+    let c = Quaternion::from_axis_angle(z, 1e-4);
+    assert!(a.slerp(c, 0.5).w.is_finite());
+}
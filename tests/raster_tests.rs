@@ -0,0 +1,281 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tiny_renderer::shading::FlatShader;
+use tiny_renderer::{
+    Camera, ImageMeta, Mesh, Point3D, Projection, RGBPixel, Rasterizer, Shader, Triangle, Vertex,
+};
+
+fn render(mesh: &Mesh, rasterizer: Rasterizer, shader: &mut impl Shader) -> ImageMeta {
+    let mut image = ImageMeta::new(200, 200).unwrap();
+    image.rasterizer = rasterizer;
+    image.draw_mesh(mesh, shader);
+    image
+}
+
+#[test]
+fn edge_functions_match_bounding_box() {
+    let (meshes, _) = Mesh::load_obj(Path::new("./data/african_head.obj")).unwrap();
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: 45.0_f32.to_radians(),
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let mut shader = FlatShader {
+        transform: camera.view_projection_matrix(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        },
+    };
+
+    let edges = render(&meshes[0], Rasterizer::EdgeFunction, &mut shader);
+    let bbox = render(&meshes[0], Rasterizer::BoundingBox, &mut shader);
+
+    let covered = edges.buffer.iter().filter(|p| p.red > 0).count();
+    let differing = edges
+        .buffer
+        .iter()
+        .zip(bbox.buffer.iter())
+        .filter(|&(a, b)| a.red != b.red)
+        .count();
+
+    // snapping to the subpixel grid only moves samples on triangle edges.
+    assert!(covered > 10_000);
+    assert!(differing * 100 < covered, "{} of {}", differing, covered);
+}
+
+struct White;
+
+impl Shader for White {
+    type Varying = ();
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], ()) {
+        let p = vertex.position;
+        ([p[0], p[1], 0.0, 1.0], ())
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        Some(RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        })
+    }
+}
+
+fn triangle(vertices: [[f32; 3]; 3]) -> Mesh {
+    Mesh::new(
+        vertices.iter().map(|&v| Point3D::new(v)).collect(),
+        vec![],
+        vec![],
+        vec![0, 1, 2],
+    )
+    .unwrap()
+}
+
+fn coverage(image: &ImageMeta) -> usize {
+    image.buffer.iter().filter(|p| p.red > 0).count()
+}
+
+#[test]
+fn both_windings_are_drawn() {
+    let ccw = triangle([[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.0, 0.5, 0.0]]);
+    let cw = triangle([[-0.5, -0.5, 0.0], [0.0, 0.5, 0.0], [0.5, -0.5, 0.0]]);
+
+    for &rasterizer in [Rasterizer::EdgeFunction, Rasterizer::BoundingBox].iter() {
+        let a = coverage(&render(&ccw, rasterizer, &mut White));
+        let b = coverage(&render(&cw, rasterizer, &mut White));
+
+        assert!(a > 0);
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn degenerate_triangles_draw_nothing() {
+    let line = triangle([[-0.5, -0.5, 0.0], [0.0, 0.0, 0.0], [0.5, 0.5, 0.0]]);
+
+    assert_eq!(
+        coverage(&render(&line, Rasterizer::EdgeFunction, &mut White)),
+        0
+    );
+}
+
+/*
+ * Watertightness: meshes tiling the whole screen must cover every pixel
+ * exactly once. Each face is drawn slightly closer than the one before, so
+ * a pixel drawn twice passes the depth test twice and shows up in the
+ * fragment count.
+ */
+struct CountingShader {
+    fragments: AtomicUsize,
+}
+
+impl Shader for CountingShader {
+    type Varying = ();
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], ()) {
+        let p = vertex.position;
+        ([p[0], p[1], -1e-3 * vertex.face as f32, 1.0], ())
+    }
+
+    fn fragment(&self, _: ()) -> Option<RGBPixel> {
+        self.fragments.fetch_add(1, Ordering::Relaxed);
+        Some(RGBPixel {
+            red: 255,
+            green: 255,
+            blue: 255,
+        })
+    }
+}
+
+const WIDTH: usize = 37;
+const HEIGHT: usize = 29;
+
+// a full screen quad split into n x n cells of two triangles each, with the
+// inner vertices placed by `inner(i, j)` in pixels.
+fn subdivided_quad<F: Fn(usize, usize) -> (f32, f32)>(n: usize, inner: F) -> Mesh {
+    let mut positions = vec![];
+    for j in 0..=n {
+        for i in 0..=n {
+            let (mut x, mut y) = (
+                WIDTH as f32 * i as f32 / n as f32,
+                HEIGHT as f32 * j as f32 / n as f32,
+            );
+            if i > 0 && i < n {
+                x = inner(i, j).0;
+            }
+            if j > 0 && j < n {
+                y = inner(i, j).1;
+            }
+            positions.push(Point3D::new([
+                2.0 * x / WIDTH as f32 - 1.0,
+                2.0 * y / HEIGHT as f32 - 1.0,
+                0.0,
+            ]));
+        }
+    }
+
+    let mut indices = vec![];
+    for j in 0..n {
+        for i in 0..n {
+            let (a, b) = (j * (n + 1) + i, j * (n + 1) + i + 1);
+            let (c, d) = (a + n + 1, b + n + 1);
+            // alternate the diagonals.
+            if (i + j) % 2 == 0 {
+                indices.extend_from_slice(&[a, b, d, a, d, c]);
+            } else {
+                indices.extend_from_slice(&[a, b, c, b, d, c]);
+            }
+        }
+    }
+    Mesh::new(positions, vec![], vec![], indices).unwrap()
+}
+
+fn assert_watertight(mesh: &Mesh) {
+    for &threads in [1, 3].iter() {
+        let mut shader = CountingShader {
+            fragments: AtomicUsize::new(0),
+        };
+        let mut image = ImageMeta::new(WIDTH, HEIGHT).unwrap();
+        image.threads = threads;
+        image.draw_mesh(mesh, &mut shader);
+
+        assert_eq!(coverage(&image), WIDTH * HEIGHT, "gaps");
+        assert_eq!(
+            shader.fragments.load(Ordering::Relaxed),
+            WIDTH * HEIGHT,
+            "overdraw"
+        );
+    }
+}
+
+#[test]
+fn grid_through_pixel_centers_is_watertight() {
+    // inner vertices on pixel centers: horizontal, vertical and diagonal
+    // edges all run through rows of samples.
+    assert_watertight(&subdivided_quad(6, |i, j| {
+        (6.0 * i as f32 + 0.5, 4.0 * j as f32 + 0.5)
+    }));
+}
+
+#[test]
+fn jittered_grid_is_watertight() {
+    // deterministic pseudo-random offsets of up to a third of a cell.
+    let jitter = |k: usize| ((k * 7919 % 101) as f32 / 101.0 - 0.5) * 0.66;
+    let n = 8;
+    let (cell_w, cell_h) = (WIDTH as f32 / n as f32, HEIGHT as f32 / n as f32);
+
+    assert_watertight(&subdivided_quad(n, |i, j| {
+        (
+            cell_w * (i as f32 + jitter(i * 31 + j)),
+            cell_h * (j as f32 + jitter(j * 17 + i + 5)),
+        )
+    }));
+}
+
+#[test]
+fn fan_is_watertight() {
+    // triangles around a shared vertex, reaching out to the screen border.
+    let mut positions = vec![Point3D::new([0.13, -0.21, 0.0])];
+    let border = [
+        [-1.0, -1.0],
+        [-0.2, -1.0],
+        [1.0, -1.0],
+        [1.0, 0.3],
+        [1.0, 1.0],
+        [0.45, 1.0],
+        [-1.0, 1.0],
+        [-1.0, -0.05],
+    ];
+    for p in border.iter() {
+        positions.push(Point3D::new([p[0], p[1], 0.0]));
+    }
+    let mut indices = vec![];
+    for k in 0..border.len() {
+        indices.extend_from_slice(&[0, 1 + k, 1 + (k + 1) % border.len()]);
+    }
+
+    assert_watertight(&Mesh::new(positions, vec![], vec![], indices).unwrap());
+}
+
+#[test]
+fn screen_space_triangles_share_edges() {
+    // two halves of an 8x8 square, split along the diagonal through pixel
+    // centers.
+    let corners = [[0.0, 0.0], [8.0, 0.0], [8.0, 8.0], [0.0, 8.0]];
+    let half = |a: usize, b: usize, c: usize| Triangle {
+        vertices: [
+            Point3D::new([corners[a][0], corners[a][1], 0.5]),
+            Point3D::new([corners[b][0], corners[b][1], 0.5]),
+            Point3D::new([corners[c][0], corners[c][1], 0.5]),
+        ],
+    };
+    let white = RGBPixel {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+
+    let mut covered = vec![0; 64];
+    for triangle in [half(0, 1, 2), half(0, 2, 3)].iter() {
+        let mut image = ImageMeta::new(8, 8).unwrap();
+        let mut zbuffer = vec![f32::MIN; 64];
+        image.draw_triangle(*triangle, &mut zbuffer, &white);
+
+        for (count, pixel) in covered.iter_mut().zip(image.buffer.iter()) {
+            *count += (pixel.red > 0) as usize;
+        }
+    }
+    assert!(covered.iter().all(|&count| count == 1), "{:?}", covered);
+}
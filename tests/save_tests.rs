@@ -0,0 +1,177 @@
+extern crate tiny_renderer;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tiny_renderer::imagefmt::{self, ColFmt};
+use tiny_renderer::netpbm;
+use tiny_renderer::{load_texture, Error, ImageMeta, RGBPixel, TextureFormat};
+
+// a 3x2 image with a distinct color per pixel.
+fn image() -> ImageMeta {
+    let mut image = ImageMeta::new(3, 2).unwrap();
+    for (i, pixel) in image.buffer.iter_mut().enumerate() {
+        *pixel = RGBPixel {
+            red: 40 * i as u8,
+            green: 255 - 40 * i as u8,
+            blue: 7,
+        };
+    }
+    image
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("tiny_renderer_{}", name))
+}
+
+fn rgb(image: &ImageMeta) -> Vec<u8> {
+    image
+        .buffer
+        .iter()
+        .flat_map(|p| vec![p.red, p.green, p.blue])
+        .collect()
+}
+
+#[test]
+fn png_and_tga_round_trip() {
+    let image = image();
+
+    for name in ["save.png", "save.tga"].iter() {
+        let path = temp_path(name);
+        image.save(&path).unwrap();
+
+        let read = imagefmt::read(&path, ColFmt::RGB).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((read.w, read.h), (3, 2));
+        assert_eq!(read.buf, rgb(&image));
+    }
+}
+
+#[test]
+fn ppm_is_binary_p6() {
+    let image = image();
+    let path = temp_path("save.ppm");
+    image.save(&path).unwrap();
+
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let header = b"P6\n3 2\n255\n";
+    assert_eq!(&bytes[..header.len()], &header[..]);
+    assert_eq!(bytes[header.len()..].to_vec(), rgb(&image));
+}
+
+#[test]
+fn short_buffers_are_invalid_input() {
+    let mut image = image();
+    image.buffer.pop();
+
+    for name in ["short.ppm", "short.pgm"].iter() {
+        match image.save(&temp_path(name)) {
+            Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::InvalidInput => {}
+            other => panic!("{}: expected invalid input, got {:?}", name, other.err()),
+        }
+        assert!(!temp_path(name).exists());
+    }
+
+    let mut bytes = vec![];
+    let err = netpbm::write_ppm(&mut bytes, 3, 2, &[0; 17]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn pgm_stores_luma() {
+    let mut image = ImageMeta::new(2, 1).unwrap();
+    image.buffer[0] = RGBPixel {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+    image.buffer[1] = RGBPixel {
+        red: 0,
+        green: 255,
+        blue: 0,
+    };
+    let path = temp_path("save.PGM");
+    image.save(&path).unwrap();
+
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(bytes, b"P5\n2 1\n255\n\xff\x95".to_vec());
+}
+
+#[test]
+fn unknown_extension_is_an_error() {
+    match image().save(&temp_path("save.xyz")) {
+        Err(Error::UnsupportedFormat(ref ext)) if ext == "xyz" => (),
+        other => panic!("expected an unsupported format, got {:?}", other),
+    }
+    match image().save(&temp_path("save")) {
+        Err(Error::UnsupportedFormat(ref ext)) if ext.is_empty() => (),
+        other => panic!("expected an unsupported format, got {:?}", other),
+    }
+}
+
+#[test]
+fn failed_encoding_keeps_the_old_file() {
+    let path = temp_path("keep.tga");
+    image().save(&path).unwrap();
+    let before = fs::read(&path).unwrap();
+
+    // TGA stores its dimensions in 16 bits.
+    let wide = ImageMeta::new(70_000, 1).unwrap();
+    assert!(wide.save(&path).is_err());
+    assert_eq!(fs::read(&path).unwrap(), before);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn io_errors_are_returned() {
+    let missing = temp_path("missing_dir");
+
+    for name in ["save.png", "save.ppm"].iter() {
+        match image().save(&missing.join(name)) {
+            Err(Error::Io(_)) => (),
+            other => panic!("expected an IO error, got {:?}", other),
+        }
+    }
+    match load_texture(&missing.join("texture.tga")) {
+        Err(Error::Io(_)) => (),
+        other => panic!("expected an IO error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn textures_load_as_rgb() {
+    let path = temp_path("texture.png");
+    image().save(&path).unwrap();
+
+    let texture = load_texture(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!((texture.width(), texture.height()), (3, 2));
+    assert_eq!(texture.format(), TextureFormat::Rgb);
+    let texels: Vec<u8> = (0..6)
+        .flat_map(|i| {
+            let p = texture.texel(0, i % 3, i / 3).to_pixel();
+            vec![p.red, p.green, p.blue]
+        })
+        .collect();
+    assert_eq!(texels, rgb(&image()));
+}
+
+#[test]
+fn empty_or_huge_framebuffer_is_an_error() {
+    let cases = [(0, 10), (10, 0), (100_000, 100_000), (usize::MAX, 2)];
+    for &(width, height) in cases.iter() {
+        match ImageMeta::new(width, height) {
+            Err(Error::InvalidDimensions { .. }) => (),
+            _ => panic!("expected {}x{} to be rejected", width, height),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+extern crate tiny_renderer;
+
+use std::error;
+use std::path::Path;
+use tiny_renderer::{Error, Format, Multisample, Point3D, SceneFile};
+
+fn parse(text: &str) -> Result<SceneFile, Error> {
+    SceneFile::parse(text, Path::new("data"))
+}
+
+// the line and message of a parse error.
+fn error(text: &str) -> (usize, String) {
+    match parse(text) {
+        Err(Error::Parse { line, message }) => (line, message),
+        Err(err) => panic!("expected a parse error, got {}", err),
+        Ok(_) => panic!("expected a parse error for {:?}", text),
+    }
+}
+
+fn apply(file: &SceneFile, node: usize, p: Point3D) -> Point3D {
+    let instances = file.scene.instances();
+    let v = instances[node].transform * [p.x, p.y, p.z, 1.0];
+    Point3D::new([v[0], v[1], v[2]])
+}
+
+#[test]
+fn example_scene_loads() {
+    let file = SceneFile::load(Path::new("data/pedestal.scene")).unwrap();
+
+    assert_eq!(file.output.path, Path::new("data/scene.png"));
+    assert_eq!(file.output.format, Format::Png);
+    assert_eq!((file.output.width, file.output.height), (400, 400));
+    assert_eq!(file.output.multisample, Multisample::X4);
+    assert!(file.scene.shadows);
+
+    let names: Vec<&str> = file
+        .scene
+        .root
+        .children
+        .iter()
+        .map(|n| n.name.as_str())
+        .collect();
+    assert_eq!(names, ["floor", "pedestal", "head"]);
+    assert_eq!(file.scene.meshes.len(), 3);
+
+    let floor = file.scene.root.find("floor").unwrap();
+    assert_eq!(floor.material.as_ref().unwrap().color.green, 160);
+}
+
+#[test]
+fn comments_and_blank_lines_are_ignored() {
+    let file =
+        parse("# a scene\n\n[light]   # the only one\n  shadows on # cast them\n\n").unwrap();
+
+    assert!(file.scene.shadows);
+    assert_eq!(file.output.path, Path::new("data/out.tga"));
+}
+
+#[test]
+fn transforms_apply_in_the_order_written() {
+    let file =
+        parse("[node a]\nscale 2\ntranslate 1 0 0\n[node b]\ntranslate 1 0 0\nscale 2\n").unwrap();
+
+    // instances are the root, then a and b.
+    let a = apply(&file, 1, Point3D::new([1.0, 0.0, 0.0]));
+    let b = apply(&file, 2, Point3D::new([1.0, 0.0, 0.0]));
+    assert_eq!(a.x, 3.0);
+    assert_eq!(b.x, 4.0);
+}
+
+#[test]
+fn nodes_hang_from_their_parents() {
+    let text = "\
+[material red]
+color 255 0 0
+
+[node table]
+material red
+translate 0 1 0
+
+[node cup]
+parent table
+translate 0.5 0 0
+
+[node lamp]
+";
+    let file = parse(text).unwrap();
+
+    let table = file.scene.root.find("table").unwrap();
+    assert_eq!(table.children.len(), 1);
+    assert_eq!(table.children[0].name, "cup");
+    assert_eq!(file.scene.root.children.len(), 2);
+
+    // the cup inherits the table's material and offset.
+    let instances = file.scene.instances();
+    let cup = instances.iter().find(|i| i.node.name == "cup").unwrap();
+    assert_eq!(cup.material.color.red, 255);
+    let origin = cup.transform * [0.0, 0.0, 0.0, 1.0];
+    assert_eq!((origin[0], origin[1]), (0.5, 1.0));
+}
+
+#[test]
+fn meshes_are_loaded_once() {
+    let file = parse("[node a]\nmesh cube.obj\n[node b]\nmesh cube.obj\n").unwrap();
+
+    assert_eq!(file.scene.meshes.len(), 1);
+    assert_eq!(file.scene.world_meshes().unwrap().len(), 2);
+}
+
+#[test]
+fn errors_give_the_line() {
+    let cases = [
+        ("eye 0 0 3\n", 1, "outside of any section"),
+        ("[camera\n", 1, "missing its ']'"),
+        ("\n[lights]\n", 2, "unknown section [lights]"),
+        ("[camera]\n[camera]\n", 2, "duplicate [camera] section"),
+        ("[output]\nsize 800\n", 2, "expected WIDTH HEIGHT"),
+        ("[output]\nmsaa 3\n", 2, "expected 1, 2, 4 or 8"),
+        ("[output]\npath out.xyz\n", 2, "cannot tell the format"),
+        (
+            "[camera]\n\n# looking down\neye 0 1\n",
+            4,
+            "expected 3 numbers",
+        ),
+        ("[camera]\nfov wide\n", 2, "'wide' is not a number"),
+        ("[camera]\nzoom 2\n", 2, "unknown camera property 'zoom'"),
+        ("[light]\nshadows yes\n", 2, "expected on or off"),
+        ("[material]\n", 1, "needs a name"),
+        ("[material m]\ncolor 300 0 0\n", 2, "0 to 255"),
+        ("[material m]\nfilter cubic\n", 2, "unknown texture filter"),
+        ("[node a]\nparent b\n[node b]\n", 2, "unknown node 'b'"),
+        ("[node a]\nmaterial stone\n", 2, "unknown material 'stone'"),
+        ("[node a]\n[node a]\n", 2, "duplicate node 'a'"),
+        ("[node a]\nrotate 0 0 0 90\n", 2, "axis must not be zero"),
+        ("[camera]\nnear 0\nfar 10\n", 2, "positive distance"),
+        ("[camera]\nfar 10\nnear 20\n[light]\n", 3, "beyond near"),
+        ("[camera]\nfov 180\neye 0 0 5\n", 2, "between 0 and 180"),
+        ("[camera]\northographic 4\nfov 0\n", 3, "between 0 and 180"),
+        ("[camera]\northographic -4\n", 2, "positive height"),
+        ("[camera]\ntarget 0 0 3\n\n", 2, "eye must differ"),
+        (
+            "[camera]\neye 0 5 0\nup 0 2 0\nnear 1\n",
+            3,
+            "up must not point",
+        ),
+    ];
+
+    for &(text, line, message) in cases.iter() {
+        let (found_line, found_message) = error(text);
+        assert_eq!(found_line, line, "{:?}", text);
+        assert!(
+            found_message.contains(message),
+            "{:?}: {}",
+            text,
+            found_message
+        );
+    }
+}
+
+#[test]
+fn errors_display_their_line() {
+    let err = parse("[camera]\nfov wide\n").err().unwrap();
+    assert_eq!(err.to_string(), "line 2: fov: 'wide' is not a number");
+}
+
+#[test]
+fn load_errors_keep_their_cause() {
+    let cases = [
+        ("[material m]\ntexture missing.png\n", "missing.png"),
+        ("[node a]\nmesh missing.obj\n", "missing.obj"),
+    ];
+
+    for &(text, file) in cases.iter() {
+        let err = parse(text).err().unwrap();
+        match err {
+            Error::Load {
+                line: 2,
+                ref path,
+                ref source,
+            } if path.ends_with(file) => match **source {
+                Error::Io(_) | Error::Obj(_) => (),
+                ref other => panic!("{:?}: unexpected cause {:?}", text, other),
+            },
+            ref other => panic!("{:?}: expected a load error, got {:?}", text, other),
+        }
+        assert!(err.to_string().starts_with("line 2: cannot load '"));
+        assert!(error::Error::source(&err).is_some());
+    }
+}
+
+#[test]
+fn renders_at_the_output_size() {
+    let file = parse("[output]\nsize 30 20\nssaa 2\n[node box]\nmesh cube.obj\n").unwrap();
+    let image = file.render().unwrap();
+
+    assert_eq!((image.width, image.height), (30, 20));
+    assert!(image.buffer.iter().any(|p| p.red > 0));
+}
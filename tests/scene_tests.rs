@@ -0,0 +1,273 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use std::sync::Arc;
+use tiny_renderer::matrix::Matrix4x4;
+use tiny_renderer::scene::Material;
+use tiny_renderer::{Camera, Error, ImageMeta, Node, Point3D, Projection, RGBPixel, Scene};
+
+fn translation(x: f32, y: f32, z: f32) -> Matrix4x4 {
+    Matrix4x4::translation(Point3D::new([x, y, z]))
+}
+
+fn colored(red: u8, green: u8, blue: u8) -> Material {
+    Material {
+        color: RGBPixel { red, green, blue },
+        ..Material::default()
+    }
+}
+
+fn apply(m: &Matrix4x4, p: Point3D) -> Point3D {
+    let v = m * [p.x, p.y, p.z, 1.0];
+    Point3D::new([v[0], v[1], v[2]])
+}
+
+fn camera() -> Camera {
+    Camera::look_at(
+        Point3D::new([1.0, 2.0, 4.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: std::f32::consts::FRAC_PI_2,
+            aspect: 1.5,
+            near: 0.1,
+            far: 20.0,
+        },
+    )
+}
+
+// a scene holding the unit cube of the data directory, not yet placed.
+fn cube_scene() -> (Scene, usize) {
+    let mut scene = Scene::new();
+    let cube = scene.load_obj(Path::new("data/cube.obj")).unwrap();
+    let index = cube.children[0].mesh.unwrap();
+    (scene, index)
+}
+
+#[test]
+fn transforms_concatenate_down_the_tree() {
+    let mut scene = Scene::new();
+    let mut arm = Node::new("arm");
+    arm.transform = translation(1.0, 0.0, 0.0);
+    let mut hand = Node::new("hand");
+    hand.transform =
+        Matrix4x4::rotation(Point3D::new([0.0, 0.0, 1.0]), std::f32::consts::FRAC_PI_2);
+    arm.add_child(hand).add_child(Node::new("finger")).transform = translation(1.0, 0.0, 0.0);
+    scene.root.add_child(arm);
+
+    let instances = scene.instances();
+    let names: Vec<&str> = instances.iter().map(|i| i.node.name.as_str()).collect();
+    assert_eq!(names, ["root", "arm", "hand", "finger"]);
+
+    // the finger is one unit along the hand's rotated x-axis, i.e. up.
+    let finger = apply(&instances[3].transform, Point3D::zero());
+    assert!((finger - Point3D::new([1.0, 1.0, 0.0])).length() < 1e-6);
+}
+
+#[test]
+fn materials_are_inherited() {
+    let mut scene = Scene::new();
+    let mut parent = Node::new("parent");
+    parent.material = Some(Arc::new(colored(255, 0, 0)));
+    parent.add_child(Node::new("plain"));
+    let mut own = Node::new("own");
+    own.material = Some(Arc::new(colored(0, 0, 255)));
+    parent.add_child(own);
+    scene.root.add_child(parent);
+
+    let colors: Vec<(String, u8, u8)> = scene
+        .instances()
+        .iter()
+        .map(|i| {
+            (
+                i.node.name.clone(),
+                i.material.color.red,
+                i.material.color.blue,
+            )
+        })
+        .collect();
+    assert_eq!(colors[0], ("root".to_string(), 255, 255));
+    assert_eq!(colors[2], ("plain".to_string(), 255, 0));
+    assert_eq!(colors[3], ("own".to_string(), 0, 255));
+}
+
+#[test]
+fn meshes_are_moved_to_world_space() {
+    let (mut scene, cube) = cube_scene();
+    let mut node = Node::new("box");
+    node.transform = translation(0.0, 2.0, 0.0) * Matrix4x4::scale(Point3D::new([4.0, 1.0, 1.0]));
+    node.mesh = Some(cube);
+    scene.root.add_child(node);
+    let source = scene.meshes[cube].positions.clone();
+
+    let world = scene.world_meshes().unwrap();
+    assert_eq!(world.len(), 1);
+
+    let mesh = &world[0].0;
+    let min_x = mesh.positions.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+    let min_y = mesh.positions.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+    assert_eq!(min_x, -2.0);
+    assert_eq!(min_y, 1.5);
+    for n in mesh.normals.iter() {
+        assert!((n.length() - 1.0).abs() < 1e-6);
+    }
+    // the source mesh is left alone.
+    assert_eq!(scene.meshes[cube].positions, source);
+}
+
+#[test]
+fn mirroring_keeps_faces_outward() {
+    let (mut scene, cube) = cube_scene();
+    let mut node = Node::new("mirrored");
+    node.transform = Matrix4x4::scale(Point3D::new([-1.0, 1.0, 1.0]));
+    node.mesh = Some(cube);
+    scene.root.add_child(node);
+
+    let world = scene.world_meshes().unwrap();
+    for face in world[0].0.faces() {
+        let v = face.triangle.vertices;
+        let center = (v[0] + v[1] + v[2]) / 3.0;
+        assert!(face.normal().dot(center) > 0.0);
+    }
+}
+
+#[test]
+fn singular_transforms_are_rejected() {
+    let (mut scene, cube) = cube_scene();
+    let mut node = Node::new("flat");
+    node.transform = Matrix4x4::scale(Point3D::new([1.0, 0.0, 1.0]));
+    node.mesh = Some(cube);
+    scene.root.add_child(node);
+
+    match scene.world_meshes() {
+        Err(Error::SingularMatrix) => {}
+        _ => panic!("expected a singular matrix error"),
+    }
+
+    let mut image = ImageMeta::new(10, 10).unwrap();
+    match scene.render(&mut image, &camera()) {
+        Err(Error::SingularMatrix) => {}
+        _ => panic!("expected a singular matrix error"),
+    }
+    assert!(image.zbuffer.iter().all(|&z| z == f32::MIN));
+}
+
+#[test]
+fn meshes_are_drawn_where_their_copies_would_be() {
+    let (mut scene, cube) = cube_scene();
+    scene.shadows = true;
+    scene.light_dir = Point3D::new([-1.0, -2.0, -1.0]);
+    let transforms = [
+        translation(-1.0, 0.0, 0.0)
+            * Matrix4x4::rotation(Point3D::new([1.0, 1.0, 0.0]), 0.7)
+            * Matrix4x4::scale(Point3D::new([0.5, 0.8, 0.6])),
+        // mirrored, flipping the winding of its faces.
+        translation(1.0, 0.0, 0.0) * Matrix4x4::scale(Point3D::new([-0.6, 0.6, 0.6])),
+        translation(0.0, -1.5, 0.0) * Matrix4x4::scale(Point3D::new([4.0, 0.2, 4.0])),
+    ];
+    for (idx, transform) in transforms.iter().enumerate() {
+        let mut node = Node::new(&idx.to_string());
+        node.transform = *transform;
+        node.mesh = Some(cube);
+        scene.root.add_child(node);
+    }
+
+    let mut copies = Scene::new();
+    copies.shadows = scene.shadows;
+    copies.light_dir = scene.light_dir;
+    for (mesh, _) in scene.world_meshes().unwrap() {
+        let mut node = Node::new(&mesh.name);
+        node.mesh = Some(copies.add_mesh(mesh));
+        copies.root.add_child(node);
+    }
+
+    let mut image = ImageMeta::new(60, 40).unwrap();
+    scene.render(&mut image, &camera()).unwrap();
+    let mut expected = ImageMeta::new(60, 40).unwrap();
+    copies.render(&mut expected, &camera()).unwrap();
+
+    let channels = |p: RGBPixel| [p.red as i32, p.green as i32, p.blue as i32];
+    let off = image
+        .buffer
+        .iter()
+        .zip(expected.buffer.iter())
+        .filter(|&(&a, &b)| {
+            channels(a)
+                .iter()
+                .zip(channels(b).iter())
+                .any(|(a, b)| (a - b).abs() > 2)
+        })
+        .count();
+    assert!(image.buffer.iter().any(|p| p.red > 0));
+    assert!(off <= 3, "{} pixels differ", off);
+}
+
+#[test]
+fn missing_meshes_are_rejected() {
+    let (mut scene, cube) = cube_scene();
+    let mut node = Node::new("ghost");
+    node.mesh = Some(cube + 1);
+    scene.root.add_child(node);
+
+    match scene.world_meshes() {
+        Err(Error::UnknownMesh { ref node, index }) if node == "ghost" && index == cube + 1 => {}
+        _ => panic!("expected an unknown mesh error"),
+    }
+}
+
+#[test]
+fn one_mesh_is_drawn_at_every_node_using_it() {
+    let (mut scene, cube) = cube_scene();
+    for (name, x, material) in [
+        ("left", -1.0, colored(255, 0, 0)),
+        ("right", 1.0, colored(0, 0, 255)),
+    ] {
+        let mut node = Node::new(name);
+        node.transform = translation(x, 0.0, 0.0);
+        node.mesh = Some(cube);
+        node.material = Some(Arc::new(material));
+        scene.root.add_child(node);
+    }
+
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -2.0,
+            right: 2.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let (width, height) = (40, 20);
+    let mut image = ImageMeta::new(width, height).unwrap();
+    scene.render(&mut image, &camera).unwrap();
+
+    // specular highlights are white, so only the dominant channel tells
+    // the cubes apart.
+    let left = image.buffer[10 * width + 10];
+    let right = image.buffer[10 * width + 30];
+    let gap = image.buffer[10 * width + 20];
+    assert!(left.red > left.blue);
+    assert!(right.blue > right.red);
+    assert_eq!((gap.red, gap.green, gap.blue), (0, 0, 0));
+}
+
+#[test]
+fn obj_files_become_subtrees() {
+    let mut scene = Scene::new();
+    let node = scene.load_obj(Path::new("data/plane.obj")).unwrap();
+
+    assert_eq!(node.name, "plane");
+    assert_eq!(node.mesh, None);
+    assert_eq!(node.children.len(), 1);
+    assert_eq!(node.children[0].mesh, Some(0));
+    assert_eq!(scene.meshes.len(), 1);
+
+    scene.root.add_child(node);
+    assert!(scene.root.find("plane").is_some());
+    assert!(scene.root.find("missing").is_none());
+}
@@ -0,0 +1,417 @@
+extern crate tiny_renderer;
+
+use std::sync::Arc;
+use tiny_renderer::matrix::Matrix4x4;
+use tiny_renderer::shading::{
+    FlatShader, GouraudShader, Material, NormalMap, PhongShader, Specular,
+};
+use tiny_renderer::{
+    Camera, Derivatives, ImageMeta, Interpolation, Mesh, Point, Point3D, Projection, RGBPixel,
+    Shader, Texture, TextureFormat, Vertex,
+};
+
+struct SolidShader {
+    discard: bool,
+}
+
+impl Shader for SolidShader {
+    type Varying = f32;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], f32) {
+        let p = vertex.position;
+        ([p[0], p[1], -p[2], 1.0], p[0])
+    }
+
+    fn fragment(&self, x: f32) -> Option<RGBPixel> {
+        if self.discard && x < 0.0 {
+            return None;
+        }
+
+        Some(RGBPixel {
+            red: 255,
+            green: 0,
+            blue: 0,
+        })
+    }
+}
+
+fn square() -> Mesh {
+    // [-0.5, 0.5]^2 at z = 0, as two counter-clockwise triangles.
+    Mesh::new(
+        vec![
+            Point3D::new([-0.5, -0.5, 0.0]),
+            Point3D::new([0.5, -0.5, 0.0]),
+            Point3D::new([0.5, 0.5, 0.0]),
+            Point3D::new([-0.5, 0.5, 0.0]),
+        ],
+        vec![],
+        vec![],
+        vec![0, 1, 2, 0, 2, 3],
+    )
+    .unwrap()
+}
+
+#[test]
+fn draw_mesh_covers_square() {
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_mesh(&square(), &mut SolidShader { discard: false });
+
+    let covered = image.buffer.iter().filter(|p| p.red == 255).count();
+
+    // the square spans half of the image in each direction.
+    assert!(
+        (10 * 10..=11 * 11).contains(&covered),
+        "covered {}",
+        covered
+    );
+    assert_eq!(image.buffer[0].red, 0);
+}
+
+#[test]
+fn discarded_fragments_leave_depth_untouched() {
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_mesh(&square(), &mut SolidShader { discard: true });
+
+    for (pixel, depth) in image.buffer.iter().zip(image.zbuffer.iter()) {
+        assert_eq!(pixel.red == 255, *depth > 0.0);
+    }
+    assert!(image.buffer.iter().any(|p| p.red == 255));
+}
+
+/*
+ * Emits the clip coordinates themselves as varyings. When they are
+ * interpolated correctly x/w and y/w reproduce the screen position of the
+ * fragment, which is written out as the red and green channels.
+ */
+struct ScreenPositionShader;
+
+impl Shader for ScreenPositionShader {
+    type Varying = Point3D;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], Point3D) {
+        // the z coordinate of the mesh is used as w.
+        let p = vertex.position;
+        let clip = [p[0] * p[2], p[1] * p[2], 0.0, p[2]];
+        (clip, Point3D::new([clip[0], clip[1], clip[3]]))
+    }
+
+    fn fragment(&self, clip: Point3D) -> Option<RGBPixel> {
+        Some(RGBPixel {
+            red: (100.0 * (clip.x / clip.z + 1.0) / 2.0) as u8,
+            green: (100.0 * (clip.y / clip.z + 1.0) / 2.0) as u8,
+            blue: 255,
+        })
+    }
+}
+
+fn max_screen_position_error(interpolation: Interpolation) -> i32 {
+    let mesh = Mesh::new(
+        vec![
+            Point3D::new([-1.0, -1.0, 1.0]),
+            Point3D::new([1.0, -1.0, 4.0]),
+            Point3D::new([-1.0, 1.0, 4.0]),
+        ],
+        vec![],
+        vec![],
+        vec![0, 1, 2],
+    )
+    .unwrap();
+
+    let mut image = ImageMeta::new(100, 100).unwrap();
+    image.interpolation = interpolation;
+    image.draw_mesh(&mesh, &mut ScreenPositionShader);
+
+    let mut max_error = 0;
+    for (idx, pixel) in image.buffer.iter().enumerate() {
+        if pixel.blue == 0 {
+            continue;
+        }
+
+        let (row, col) = ((idx / 100) as i32, (idx % 100) as i32);
+        max_error = max_error
+            .max((pixel.red as i32 - col).abs())
+            .max((pixel.green as i32 - (100 - row)).abs());
+    }
+
+    max_error
+}
+
+#[test]
+fn perspective_correct_interpolation() {
+    assert!(max_screen_position_error(Interpolation::Perspective) <= 1);
+    assert!(max_screen_position_error(Interpolation::Affine) > 5);
+}
+
+#[test]
+fn gouraud_interpolates_vertex_lighting() {
+    // a single face whose vertex normals fan out from facing the light to
+    // facing away from it.
+    let positions = vec![
+        Point3D::new([-1.0, -1.0, 0.0]),
+        Point3D::new([1.0, -1.0, 0.0]),
+        Point3D::new([-1.0, 1.0, 0.0]),
+    ];
+    let normals = vec![
+        Point3D::new([0.0, 0.0, 1.0]),
+        Point3D::new([1.0, 0.0, 0.0]),
+        Point3D::new([1.0, 0.0, 0.0]),
+    ];
+    let mesh = Mesh::new(positions, normals, vec![], vec![0, 1, 2]).unwrap();
+    let white = RGBPixel {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    let mut flat = FlatShader {
+        transform: Matrix4x4::identity(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: white,
+    };
+    image.draw_mesh(&mesh, &mut flat);
+
+    // the flat shader only sees the face normal, which faces the light.
+    let lit: Vec<u8> = image
+        .buffer
+        .iter()
+        .map(|p| p.red)
+        .filter(|&r| r > 0)
+        .collect();
+    assert!(lit.iter().all(|&r| r == 255));
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    let mut gouraud = GouraudShader {
+        transform: Matrix4x4::identity(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: white,
+    };
+    image.draw_mesh(&mesh, &mut gouraud);
+
+    // bright in the lower left corner, fading out towards the hypotenuse.
+    let width = image.width as usize;
+    let lower_left = image.buffer[19 * width].red;
+    let middle = image.buffer[14 * width + 5].red;
+    assert!(lower_left > 240);
+    assert!(middle > 0 && middle < lower_left);
+}
+
+fn render_phong(light_dir: Point3D, specular: Specular) -> ImageMeta {
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: 90.0_f32.to_radians(),
+            aspect: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+
+    let mut shader = PhongShader::new(&camera, light_dir);
+    shader.specular = specular;
+    shader.material = Material {
+        ambient: 0.1,
+        diffuse: 0.5,
+        specular: 0.5,
+        shininess: 16.0,
+    };
+
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    // the square fills the middle of the screen and faces the camera.
+    let mut quad = square();
+    for p in quad.positions.iter_mut() {
+        *p = 4.0 * *p;
+    }
+    image.draw_mesh(&quad, &mut shader);
+    image
+}
+
+#[test]
+fn phong_highlight_faces_the_light() {
+    let light = Point3D::new([0.0, 0.0, -1.0]);
+
+    for &model in [Specular::Phong, Specular::BlinnPhong].iter() {
+        let image = render_phong(light, model);
+
+        let center = image.buffer[20 * 40 + 20].red;
+        let off_center = image.buffer[30 * 40 + 30].red;
+
+        // ambient + diffuse + specular all peak where the normal, the
+        // light and the view direction line up.
+        assert_eq!(center, 255);
+        assert!(off_center < center);
+        assert!(off_center as f32 >= 255.0 * 0.6 - 1.0);
+    }
+
+    // for the same exponent the Blinn-Phong lobe is wider.
+    let phong = render_phong(light, Specular::Phong).buffer[30 * 40 + 30].red;
+    let blinn = render_phong(light, Specular::BlinnPhong).buffer[30 * 40 + 30].red;
+    assert!(blinn > phong);
+}
+
+#[test]
+fn phong_lit_from_behind_is_ambient() {
+    let image = render_phong(Point3D::new([0.0, 0.0, 1.0]), Specular::Phong);
+
+    assert_eq!(image.buffer[20 * 40 + 20].red, (255.0 * 0.1) as u8);
+}
+
+// a 1x1 map holding a single encoded normal.
+fn normal_map(normal: [u8; 3]) -> Texture {
+    Texture::new(1, 1, TextureFormat::Rgb, normal.to_vec()).unwrap()
+}
+
+fn render_normal_mapped(map: Option<NormalMap>) -> ImageMeta {
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -1.0,
+            right: 1.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let mut quad = square();
+    quad.texcoords = vec![Point { x: 0.0, y: 0.0 }; 4];
+    quad.texcoords[1].x = 1.0;
+    quad.texcoords[2] = Point { x: 1.0, y: 1.0 };
+    quad.texcoords[3].y = 1.0;
+    quad.compute_tangents();
+
+    let mut shader = PhongShader::new(&camera, Point3D::new([0.0, 0.0, -1.0]));
+    shader.material.specular = 0.0;
+    shader.normal_map = map.map(Arc::new);
+
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_mesh(&quad, &mut shader);
+    image
+}
+
+#[test]
+fn flat_tangent_space_map_keeps_normals() {
+    let plain = render_normal_mapped(None);
+    let mapped = render_normal_mapped(Some(NormalMap::TangentSpace(normal_map([128, 128, 255]))));
+
+    assert_eq!(
+        plain.buffer[10 * 20 + 10].red,
+        mapped.buffer[10 * 20 + 10].red
+    );
+}
+
+#[test]
+fn normal_maps_tilt_the_surface() {
+    let plain = render_normal_mapped(None).buffer[10 * 20 + 10].red;
+
+    // tilted 45 degrees along the tangent (+x) in tangent space.
+    let tangent = render_normal_mapped(Some(NormalMap::TangentSpace(normal_map([218, 128, 218]))))
+        .buffer[10 * 20 + 10]
+        .red;
+    assert!(tangent < plain);
+    assert!(tangent > 0);
+
+    // pointing away from the light in object space leaves only ambient.
+    let object = render_normal_mapped(Some(NormalMap::ObjectSpace(normal_map([128, 128, 0]))))
+        .buffer[10 * 20 + 10]
+        .red;
+    assert_eq!(object, (255.0 * 0.1) as u8);
+}
+
+/*
+ * Writes the screen space derivatives of the mesh's x and y coordinates,
+ * scaled by 1000, as the red and green channels. Like
+ * `ScreenPositionShader` it takes the z coordinate of the mesh as w.
+ */
+struct DerivativeShader;
+
+impl Shader for DerivativeShader {
+    type Varying = Point;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], Point) {
+        let p = vertex.position;
+        (
+            [p[0] * p[2], p[1] * p[2], 0.0, p[2]],
+            Point { x: p[0], y: p[1] },
+        )
+    }
+
+    fn fragment(&self, _: Point) -> Option<RGBPixel> {
+        panic!("the rasterizer must pass derivatives")
+    }
+
+    const USES_DERIVATIVES: bool = true;
+
+    fn fragment_with_derivatives(&self, _: Point, d: Derivatives<Point>) -> Option<RGBPixel> {
+        Some(RGBPixel {
+            red: (1000.0 * d.dx.x).round() as u8,
+            green: (1000.0 * d.dy.y).round() as u8,
+            blue: 255,
+        })
+    }
+}
+
+fn render_derivatives(positions: Vec<Point3D>, indices: Vec<usize>) -> ImageMeta {
+    let mesh = Mesh::new(positions, vec![], vec![], indices).unwrap();
+    let mut image = ImageMeta::new(100, 100).unwrap();
+    image.draw_mesh(&mesh, &mut DerivativeShader);
+    image
+}
+
+#[test]
+fn derivatives_are_per_pixel_rates() {
+    // the whole screen at w = 1: x and y grow by 2 / 100 per pixel.
+    let image = render_derivatives(
+        vec![
+            Point3D::new([-1.0, -1.0, 1.0]),
+            Point3D::new([1.0, -1.0, 1.0]),
+            Point3D::new([1.0, 1.0, 1.0]),
+            Point3D::new([-1.0, 1.0, 1.0]),
+        ],
+        vec![0, 1, 2, 0, 2, 3],
+    );
+
+    assert!(image.buffer.iter().all(|p| p.red == 20 && p.green == 20));
+}
+
+#[test]
+fn derivatives_are_shared_within_quads() {
+    // receding to the upper right, so the rates change across the image.
+    let image = render_derivatives(
+        vec![
+            Point3D::new([-1.0, -1.0, 1.0]),
+            Point3D::new([1.0, -1.0, 4.0]),
+            Point3D::new([-1.0, 1.0, 4.0]),
+        ],
+        vec![0, 1, 2],
+    );
+    // pixel (x, y), counting y from the bottom.
+    let pixel = |x: usize, y: usize| image.buffer[(99 - y) * 100 + x];
+
+    let mut rates = vec![];
+    for y in (0..100).step_by(2) {
+        for x in (0..100).step_by(2) {
+            let quad = [
+                pixel(x, y),
+                pixel(x + 1, y),
+                pixel(x, y + 1),
+                pixel(x + 1, y + 1),
+            ];
+            let covered: Vec<_> = quad.iter().filter(|p| p.blue == 255).collect();
+            if let Some(first) = covered.first() {
+                assert!(covered
+                    .iter()
+                    .all(|p| (p.red, p.green) == (first.red, first.green)));
+                rates.push(first.red);
+            }
+        }
+    }
+
+    rates.sort();
+    rates.dedup();
+    assert!(rates.len() > 5);
+}
@@ -0,0 +1,118 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::shading::PhongShader;
+use tiny_renderer::shadow::ShadowMap;
+use tiny_renderer::{Camera, ImageMeta, Mesh, Point3D, Projection};
+
+// a square of half-width `r` parallel to the xy-plane, facing +z.
+fn square(r: f32, z: f32) -> Mesh {
+    Mesh::new(
+        vec![
+            Point3D::new([-r, -r, z]),
+            Point3D::new([r, -r, z]),
+            Point3D::new([r, r, z]),
+            Point3D::new([-r, r, z]),
+        ],
+        vec![],
+        vec![],
+        vec![0, 1, 2, 0, 2, 3],
+    )
+    .unwrap()
+}
+
+// a directional light shining straight down the z-axis.
+fn light() -> Camera {
+    Camera::look_at(
+        Point3D::new([0.0, 0.0, 2.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -1.0,
+            right: 1.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 5.0,
+        },
+    )
+}
+
+// a floor with a smaller occluder floating above its center.
+fn shadow_map() -> ShadowMap {
+    ShadowMap::render(&light(), &[square(1.0, 0.0), square(0.25, 0.5)], 64, 64).unwrap()
+}
+
+#[test]
+fn occluded_points_are_in_shadow() {
+    let map = shadow_map();
+
+    assert_eq!(map.visibility(Point3D::zero()), 0.0);
+    assert_eq!(map.visibility(Point3D::new([0.8, 0.8, 0.0])), 1.0);
+    assert_eq!(map.visibility(Point3D::new([-0.1, 0.2, 0.0])), 0.0);
+}
+
+#[test]
+fn bias_prevents_self_shadowing() {
+    let mut map = shadow_map();
+
+    // the occluder and the visible floor are the nearest surfaces.
+    assert_eq!(map.visibility(Point3D::new([0.0, 0.0, 0.5])), 1.0);
+    assert_eq!(map.visibility(Point3D::new([0.6, -0.7, 0.0])), 1.0);
+
+    // a point slightly below the floor is behind it.
+    map.bias = 0.0;
+    assert_eq!(map.visibility(Point3D::new([0.6, -0.7, -0.1])), 0.0);
+}
+
+#[test]
+fn outside_the_light_is_lit() {
+    let map = shadow_map();
+
+    assert_eq!(map.visibility(Point3D::new([3.0, 0.0, 0.0])), 1.0);
+    assert_eq!(map.visibility(Point3D::new([0.0, 0.0, 4.0])), 1.0);
+}
+
+#[test]
+fn pcf_softens_shadow_edges() {
+    let mut map = shadow_map();
+    let edge = Point3D::new([0.25, 0.0, 0.0]);
+
+    let hard = map.visibility(edge);
+    assert!(hard == 0.0 || hard == 1.0);
+
+    map.pcf = Some(2);
+    let soft = map.visibility(edge);
+    assert!(soft > 0.0 && soft < 1.0);
+
+    // well inside and outside the shadow the filter changes nothing.
+    assert_eq!(map.visibility(Point3D::zero()), 0.0);
+    assert_eq!(map.visibility(Point3D::new([0.8, 0.8, 0.0])), 1.0);
+}
+
+#[test]
+fn phong_shadowed_fragments_are_ambient() {
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -1.0,
+            right: 1.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+
+    let mut shader = PhongShader::new(&camera, Point3D::new([0.0, 0.0, -1.0]));
+    shader.material.specular = 0.0;
+    shader.shadow = Some(shadow_map());
+
+    // only the floor is drawn, the occluder just casts its shadow.
+    let mut image = ImageMeta::new(20, 20).unwrap();
+    image.draw_mesh(&square(1.0, 0.0), &mut shader);
+
+    assert_eq!(image.buffer[10 * 20 + 10].red, (255.0 * 0.1) as u8);
+    assert_eq!(image.buffer[2 * 20 + 2].red, (255.0 * 0.9) as u8);
+}
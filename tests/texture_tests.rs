@@ -0,0 +1,256 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::imagefmt::{ColFmt, Image};
+use tiny_renderer::{Error, Point, Rgba, Texture, TextureFilter, TextureFormat, Wrap};
+
+fn uv(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+
+// a gray ramp of 4 x 1 texels: 0, 60, 120 and 180.
+fn ramp(wrap: Wrap, filter: TextureFilter) -> Texture {
+    let mut texture = Texture::new(4, 1, TextureFormat::Gray, vec![0, 60, 120, 180]).unwrap();
+    texture.wrap = wrap;
+    texture.filter = filter;
+    texture
+}
+
+fn gray(texture: &Texture, u: f32) -> u8 {
+    texture.sample(uv(u, 0.5)).to_pixel().red
+}
+
+#[test]
+fn nearest_picks_the_texel_under_the_coordinate() {
+    let texture = ramp(Wrap::ClampToEdge, TextureFilter::Nearest);
+
+    assert_eq!(gray(&texture, 0.1), 0);
+    assert_eq!(gray(&texture, 0.3), 60);
+    assert_eq!(gray(&texture, 0.74), 120);
+    assert_eq!(gray(&texture, 0.99), 180);
+}
+
+#[test]
+fn bilinear_blends_between_texel_centers() {
+    let texture = ramp(Wrap::ClampToEdge, TextureFilter::Bilinear);
+
+    // on the centers, at 1/8, 3/8, ...
+    assert_eq!(gray(&texture, 0.125), 0);
+    assert_eq!(gray(&texture, 0.375), 60);
+    // halfway and a quarter way between them.
+    assert_eq!(gray(&texture, 0.25), 30);
+    assert_eq!(gray(&texture, 0.4375), 75);
+}
+
+#[test]
+fn rows_count_from_the_top() {
+    let mut texture = Texture::new(1, 2, TextureFormat::Gray, vec![200, 100]).unwrap();
+    assert_eq!(texture.sample(uv(0.5, 0.9)).red, 200.0 / 255.0);
+    assert_eq!(texture.sample(uv(0.5, 0.1)).red, 100.0 / 255.0);
+
+    texture.filter = TextureFilter::Bilinear;
+    assert_eq!(texture.sample(uv(0.5, 0.5)).to_pixel().red, 150);
+}
+
+#[test]
+fn wrap_modes_outside_the_unit_square() {
+    let nearest = |wrap: Wrap, u: f32| gray(&ramp(wrap, TextureFilter::Nearest), u);
+
+    // texel -1 and texel 4.
+    assert_eq!(nearest(Wrap::Repeat, -0.1), 180);
+    assert_eq!(nearest(Wrap::Repeat, 1.1), 0);
+    assert_eq!(nearest(Wrap::ClampToEdge, -0.1), 0);
+    assert_eq!(nearest(Wrap::ClampToEdge, 1.1), 180);
+    assert_eq!(nearest(Wrap::MirroredRepeat, -0.1), 0);
+    assert_eq!(nearest(Wrap::MirroredRepeat, 1.1), 180);
+    // the mirrored copy runs backwards, then the next one forwards again.
+    assert_eq!(nearest(Wrap::MirroredRepeat, 1.8), 0);
+    assert_eq!(nearest(Wrap::MirroredRepeat, 2.3), 60);
+    assert_eq!(nearest(Wrap::Repeat, 2.3), 60);
+
+    let red = Rgba {
+        red: 1.0,
+        green: 0.0,
+        blue: 0.0,
+        alpha: 0.5,
+    };
+    let border = ramp(Wrap::Border(red), TextureFilter::Nearest);
+    assert_eq!(border.sample(uv(1.1, 0.5)), red);
+    assert_eq!(border.sample(uv(0.5, -0.1)), red);
+    assert_eq!(gray(&border, 0.9), 180);
+}
+
+#[test]
+fn bilinear_filtering_wraps_at_the_edges() {
+    // a quarter texel left of the first center.
+    let bilinear = |wrap: Wrap| gray(&ramp(wrap, TextureFilter::Bilinear), 1.0 / 16.0);
+
+    assert_eq!(bilinear(Wrap::ClampToEdge), 0);
+    assert_eq!(bilinear(Wrap::Repeat), 45);
+    assert_eq!(bilinear(Wrap::MirroredRepeat), 0);
+
+    let white = Rgba {
+        red: 1.0,
+        green: 1.0,
+        blue: 1.0,
+        alpha: 1.0,
+    };
+    assert_eq!(bilinear(Wrap::Border(white)), 64);
+}
+
+#[test]
+fn every_format_reads_as_rgba() {
+    let gray = Texture::new(1, 1, TextureFormat::Gray, vec![51]).unwrap();
+    let rgb = Texture::new(1, 1, TextureFormat::Rgb, vec![51, 102, 153]).unwrap();
+    let rgba = Texture::new(1, 1, TextureFormat::Rgba, vec![51, 102, 153, 204]).unwrap();
+
+    let channels = |c: Rgba| (c.red, c.green, c.blue, c.alpha);
+    assert_eq!(channels(gray.texel(0, 0, 0)), (0.2, 0.2, 0.2, 1.0));
+    assert_eq!(channels(rgb.texel(0, 0, 0)), (0.2, 0.4, 0.6, 1.0));
+    assert_eq!(channels(rgba.texel(0, 0, 0)), (0.2, 0.4, 0.6, 0.8));
+}
+
+#[test]
+fn images_convert_to_the_closest_format() {
+    let image = |fmt: ColFmt, buf: Vec<u8>| {
+        Texture::from_image(Image {
+            w: 1,
+            h: 1,
+            fmt,
+            buf,
+        })
+        .unwrap()
+    };
+
+    assert_eq!(image(ColFmt::Y, vec![7]).format(), TextureFormat::Gray);
+    assert_eq!(
+        image(ColFmt::RGB, vec![1, 2, 3]).format(),
+        TextureFormat::Rgb
+    );
+
+    let bgr = image(ColFmt::BGR, vec![1, 2, 3]);
+    assert_eq!(bgr.format(), TextureFormat::Rgb);
+    assert_eq!(bgr.texel(0, 0, 0).to_pixel().red, 3);
+
+    let gray_alpha = image(ColFmt::YA, vec![7, 9]);
+    assert_eq!(gray_alpha.format(), TextureFormat::Rgba);
+    assert_eq!(gray_alpha.texel(0, 0, 0).alpha, 9.0 / 255.0);
+}
+
+#[test]
+fn texels_must_fill_the_texture() {
+    match Texture::new(2, 2, TextureFormat::Rgb, vec![0; 9]) {
+        Err(Error::TexelCount {
+            expected: 12,
+            found: 9,
+        }) => (),
+        _ => panic!("expected a short texel buffer to be rejected"),
+    }
+    match Texture::new(0, 2, TextureFormat::Gray, vec![]) {
+        Err(Error::InvalidDimensions { .. }) => (),
+        _ => panic!("expected an empty texture to be rejected"),
+    }
+}
+
+// an 8 x 8 gray texture, white where `white(i, j)` holds for texel (i, j).
+fn pattern<F: Fn(usize, usize) -> bool>(white: F, filter: TextureFilter) -> Texture {
+    let mut texels = vec![];
+    for j in 0..8 {
+        for i in 0..8 {
+            texels.push(if white(i, j) { 255 } else { 0 });
+        }
+    }
+    let mut texture = Texture::new(8, 8, TextureFormat::Gray, texels).unwrap();
+    texture.filter = filter;
+    texture.generate_mipmaps();
+    texture
+}
+
+fn checker(filter: TextureFilter) -> Texture {
+    pattern(|i, j| (i + j) % 2 == 0, filter)
+}
+
+#[test]
+fn mip_chains_halve_down_to_one_texel() {
+    let texture = checker(TextureFilter::Nearest);
+    let sizes: Vec<_> = (0..texture.levels())
+        .map(|l| texture.level_size(l))
+        .collect();
+    assert_eq!(sizes, vec![(8, 8), (4, 4), (2, 2), (1, 1)]);
+
+    // every 2x2 block of the checker is half white.
+    for level in 1..texture.levels() {
+        assert_eq!(texture.texel(level, 0, 0).to_pixel().red, 128);
+    }
+
+    let mut odd = Texture::new(5, 3, TextureFormat::Rgb, vec![0; 45]).unwrap();
+    odd.generate_mipmaps();
+    assert_eq!(odd.levels(), 3);
+    assert_eq!(odd.level_size(1), (2, 1));
+    assert_eq!(odd.level_size(2), (1, 1));
+}
+
+#[test]
+fn trilinear_follows_the_footprint() {
+    let texture = checker(TextureFilter::Trilinear);
+    // the center of texel (4, 4), which is white.
+    let center = uv(4.5 / 8.0, 3.5 / 8.0);
+    let sample = |texels: f32| {
+        let d = texels / 8.0;
+        let color = texture.sample_grad(center, uv(d, 0.0), uv(0.0, d));
+        color.to_pixel().red
+    };
+
+    // one texel per pixel or less reads the full size image.
+    assert_eq!(sample(0.5), 255);
+    assert_eq!(sample(1.0), 255);
+    // two texels per pixel read the next level, where the checker averages
+    // out; in between the levels are blended.
+    assert_eq!(sample(2.0), 128);
+    assert_eq!(sample(2.0_f32.sqrt()), 192);
+    // far beyond the last level.
+    assert_eq!(sample(100.0), 128);
+
+    // without derivatives, and with nearest or bilinear filtering, the
+    // footprint is ignored.
+    assert_eq!(texture.sample(center).to_pixel().red, 255);
+    let bilinear = checker(TextureFilter::Bilinear);
+    let d = uv(2.0, 2.0);
+    assert_eq!(bilinear.sample_grad(center, d, d).to_pixel().red, 255);
+}
+
+#[test]
+fn trilinear_without_mipmaps_is_bilinear() {
+    let mut texture = ramp(Wrap::ClampToEdge, TextureFilter::Trilinear);
+    let far = uv(1.0, 0.0);
+
+    assert_eq!(texture.levels(), 1);
+    assert_eq!(
+        texture.sample_grad(uv(0.25, 0.5), far, far).to_pixel().red,
+        30
+    );
+
+    texture.generate_mipmaps();
+    assert_eq!(texture.levels(), 3);
+}
+
+#[test]
+fn anisotropic_keeps_detail_across_oblique_footprints() {
+    // horizontal stripes, one texel high; row 4 is white.
+    let stripes = |filter: TextureFilter| pattern(|_, j| j % 2 == 0, filter);
+    let center = uv(4.5 / 8.0, 3.5 / 8.0);
+    // a footprint 8 texels wide and 1 texel high, along the stripes.
+    let (dx, dy) = (uv(1.0, 0.0), uv(0.0, 1.0 / 8.0));
+    let sample = |texture: Texture| texture.sample_grad(center, dx, dy).to_pixel().red;
+
+    // trilinear sizes the footprint by its long axis and blurs the stripes.
+    assert_eq!(sample(stripes(TextureFilter::Trilinear)), 128);
+    assert_eq!(sample(stripes(TextureFilter::Anisotropic(1))), 128);
+    // enough probes along it keep them sharp.
+    assert_eq!(sample(stripes(TextureFilter::Anisotropic(8))), 255);
+    assert_eq!(sample(stripes(TextureFilter::Anisotropic(16))), 255);
+
+    // across the stripes the probes average them.
+    let across = stripes(TextureFilter::Anisotropic(8));
+    let color = across.sample_grad(center, uv(0.0, 1.0), uv(1.0 / 8.0, 0.0));
+    assert!((96..160).contains(&color.to_pixel().red));
+}
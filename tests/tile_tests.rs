@@ -0,0 +1,105 @@
+extern crate tiny_renderer;
+
+use std::path::Path;
+use tiny_renderer::shading::{GouraudShader, PhongShader};
+use tiny_renderer::shadow::ShadowMap;
+use tiny_renderer::{Camera, ImageMeta, Mesh, Point3D, Projection, RGBPixel, Shader};
+
+fn head() -> Vec<Mesh> {
+    Mesh::load_obj(Path::new("./data/african_head.obj"))
+        .unwrap()
+        .0
+}
+
+fn camera(width: usize, height: usize) -> Camera {
+    Camera::look_at(
+        Point3D::new([0.5, 0.3, 2.5]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: 50.0_f32.to_radians(),
+            aspect: width as f32 / height as f32,
+            near: 0.1,
+            far: 10.0,
+        },
+    )
+}
+
+fn render<S: Shader>(
+    meshes: &[Mesh],
+    shader: &mut S,
+    width: usize,
+    height: usize,
+    threads: usize,
+) -> ImageMeta {
+    let mut image = ImageMeta::new(width, height).unwrap();
+    image.threads = threads;
+    for mesh in meshes.iter() {
+        image.draw_mesh(mesh, shader);
+    }
+    image
+}
+
+fn assert_same(a: &ImageMeta, b: &ImageMeta) {
+    let rgb = |p: &RGBPixel| (p.red, p.green, p.blue);
+
+    assert!(a.buffer.iter().map(rgb).eq(b.buffer.iter().map(rgb)));
+    // compare bit patterns, the empty z-buffer holds f32::MIN.
+    assert!(a
+        .zbuffer
+        .iter()
+        .map(|z| z.to_bits())
+        .eq(b.zbuffer.iter().map(|z| z.to_bits())));
+}
+
+#[test]
+fn tiled_gouraud_matches_single_thread() {
+    let meshes = head();
+
+    // sizes that do and do not divide into whole bands.
+    for &(width, height) in [(128, 128), (203, 157), (40, 10)].iter() {
+        let mut shader = GouraudShader {
+            transform: camera(width, height).view_projection_matrix(),
+            light_dir: Point3D::new([0.0, 0.0, -1.0]),
+            color: RGBPixel {
+                red: 200,
+                green: 180,
+                blue: 160,
+            },
+        };
+        let serial = render(&meshes, &mut shader, width, height, 1);
+
+        assert!(serial.buffer.iter().any(|p| p.red > 0));
+        for &threads in [2, 3, 8].iter() {
+            assert_same(
+                &serial,
+                &render(&meshes, &mut shader, width, height, threads),
+            );
+        }
+    }
+}
+
+#[test]
+fn tiled_phong_with_shadows_matches_single_thread() {
+    let meshes = head();
+    let (width, height) = (160, 120);
+
+    let light = Camera::look_at(
+        Point3D::new([2.0, 2.0, 2.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -1.5,
+            right: 1.5,
+            bottom: -1.5,
+            top: 1.5,
+            near: 0.1,
+            far: 10.0,
+        },
+    );
+    let mut shader = PhongShader::new(&camera(width, height), Point3D::new([-1.0, -1.0, -1.0]));
+    shader.shadow = Some(ShadowMap::render(&light, &meshes, 256, 256).unwrap());
+
+    let serial = render(&meshes, &mut shader, width, height, 1);
+    assert_same(&serial, &render(&meshes, &mut shader, width, height, 4));
+}
@@ -0,0 +1,147 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::matrix::Matrix4x4;
+use tiny_renderer::typenum::U5;
+use tiny_renderer::{
+    ImageMeta, Mesh, Point, Point3D, RGBPixel, Shader, Vec2, Vec3, Vec4, Vector, Vertex,
+};
+
+#[test]
+fn arithmetic_is_componentwise() {
+    let a = Vec3::new(1.0, 2.0, 3.0);
+    let b = Vec3::new(4.0, -5.0, 6.0);
+
+    assert_eq!(a + b, Vec3::new(5.0, -3.0, 9.0));
+    assert_eq!(a - b, Vec3::new(-3.0, 7.0, -3.0));
+    assert_eq!(a * b, Vec3::new(4.0, -10.0, 18.0));
+    assert_eq!(b / a, Vec3::new(4.0, -2.5, 2.0));
+    assert_eq!(-a, Vec3::new(-1.0, -2.0, -3.0));
+
+    assert_eq!(a * 2.0, Vec3::new(2.0, 4.0, 6.0));
+    assert_eq!(2.0 * a, a * 2.0);
+    assert_eq!(a / 2.0, Vec3::new(0.5, 1.0, 1.5));
+    assert_eq!(a + 1.0, Vec3::new(2.0, 3.0, 4.0));
+    assert_eq!(a - 1.0, Vec3::new(0.0, 1.0, 2.0));
+
+    let mut c = a;
+    c += b;
+    c -= a;
+    c *= 3.0;
+    c /= 2.0;
+    assert_eq!(c, b * 1.5);
+}
+
+#[test]
+fn products_and_lengths() {
+    let x = Vec3::new(1.0, 0.0, 0.0);
+    let y = Vec3::new(0.0, 1.0, 0.0);
+
+    assert_eq!(x.dot(y), 0.0);
+    assert_eq!(x.cross(y), Vec3::new(0.0, 0.0, 1.0));
+    assert_eq!(y.cross(x), Vec3::new(0.0, 0.0, -1.0));
+
+    let v = Vec2::new(3.0, 4.0);
+    assert_eq!(v.length_squared(), 25.0);
+    assert_eq!(v.length(), 5.0);
+    assert_eq!(v.normalized(), Vec2::new(0.6, 0.8));
+    assert!(Vec2::zero().normalized()[0].is_nan());
+}
+
+#[test]
+fn lerp_min_and_max() {
+    let a = Vec4::new(0.0, 10.0, -2.0, 1.0);
+    let b = Vec4::new(4.0, 0.0, 2.0, 1.0);
+
+    assert_eq!(a.lerp(b, 0.0), a);
+    assert_eq!(a.lerp(b, 1.0), b);
+    assert_eq!(a.lerp(b, 0.25), Vec4::new(1.0, 7.5, -1.0, 1.0));
+    assert_eq!(a.min(b), Vec4::new(0.0, 0.0, -2.0, 1.0));
+    assert_eq!(a.max(b), Vec4::new(4.0, 10.0, 2.0, 1.0));
+}
+
+#[test]
+fn homogeneous_coordinates() {
+    let p = Vec3::new(1.0, 2.0, 3.0);
+
+    assert_eq!(p.to_homogeneous(), Vec4::new(1.0, 2.0, 3.0, 1.0));
+    assert_eq!(p.extend(0.0), Vec4::new(1.0, 2.0, 3.0, 0.0));
+    assert_eq!(Vec4::new(2.0, 4.0, 6.0, 2.0).from_homogeneous(), p);
+    assert_eq!(Vec4::new(2.0, 4.0, 6.0, 2.0).truncate(), p * 2.0);
+
+    // through a matrix and back.
+    let mut translate = Matrix4x4::identity();
+    translate.set(0, 3, 5.0);
+    assert_eq!(
+        (translate * p.to_homogeneous()).from_homogeneous(),
+        Vec3::new(6.0, 2.0, 3.0)
+    );
+}
+
+#[test]
+fn any_length_works() {
+    let v: Vector<U5> = Vector::from_slice(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+    assert_eq!(Vector::<U5>::from_slice(&[1.0, 2.0]), None);
+
+    assert_eq!(v.dot(Vector::splat(1.0)), 15.0);
+    assert_eq!(v.to_homogeneous().as_slice().len(), 6);
+    assert_eq!(v.truncate().as_slice(), &[1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn conversions_to_points_and_arrays() {
+    let p = Point3D::new([1.0, 2.0, 3.0]);
+    assert_eq!(Point3D::from(Vec3::from(p)), p);
+
+    let uv = Point { x: 0.25, y: 0.5 };
+    assert_eq!(Point::from(Vec2::from(uv)), uv);
+
+    let clip = [1.0, 2.0, 3.0, 4.0];
+    assert_eq!(<[f32; 4]>::from(Vec4::from(clip)), clip);
+}
+
+/*
+ * Interpolates the vertex positions as a `Vec3` and writes them out as
+ * colors, red growing to the right and green upwards.
+ */
+struct ColorShader;
+
+impl Shader for ColorShader {
+    type Varying = Vec3;
+
+    fn vertex(&mut self, vertex: &Vertex) -> ([f32; 4], Vec3) {
+        let p = vertex.position;
+        (p.to_homogeneous().into(), (p + 1.0) / 2.0)
+    }
+
+    fn fragment(&self, color: Vec3) -> Option<RGBPixel> {
+        let color = color * 255.0;
+        Some(RGBPixel {
+            red: color[0] as u8,
+            green: color[1] as u8,
+            blue: 255,
+        })
+    }
+}
+
+#[test]
+fn vectors_are_varyings() {
+    let mesh = Mesh::new(
+        vec![
+            Point3D::new([-1.0, -1.0, 0.0]),
+            Point3D::new([1.0, -1.0, 0.0]),
+            Point3D::new([1.0, 1.0, 0.0]),
+            Point3D::new([-1.0, 1.0, 0.0]),
+        ],
+        vec![],
+        vec![],
+        vec![0, 1, 2, 0, 2, 3],
+    )
+    .unwrap();
+    let mut image = ImageMeta::new(10, 10).unwrap();
+    image.draw_mesh(&mesh, &mut ColorShader);
+
+    // the top left and bottom right pixel centers.
+    let (top_left, bottom_right) = (image.buffer[0], image.buffer[99]);
+    assert_eq!((top_left.red, top_left.green), (12, 242));
+    assert_eq!((bottom_right.red, bottom_right.green), (242, 12));
+}
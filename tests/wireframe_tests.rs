@@ -0,0 +1,183 @@
+extern crate tiny_renderer;
+
+use tiny_renderer::shading::FlatShader;
+use tiny_renderer::{
+    Camera, ImageMeta, Mesh, Point3D, Projection, RGBPixel, Wireframe, WireframeMode,
+};
+
+const WHITE: RGBPixel = RGBPixel {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+
+const GREEN: RGBPixel = RGBPixel {
+    red: 0,
+    green: 255,
+    blue: 0,
+};
+
+fn camera() -> Camera {
+    Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Orthographic {
+            left: -1.0,
+            right: 1.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 10.0,
+        },
+    )
+}
+
+// squares of half-width `r` parallel to the xy-plane, as one mesh.
+fn squares(squares: &[(f32, f32)]) -> Mesh {
+    let mut positions = vec![];
+    let mut indices = vec![];
+    for &(r, z) in squares.iter() {
+        let base = positions.len();
+        positions.extend_from_slice(&[
+            Point3D::new([-r, -r, z]),
+            Point3D::new([r, -r, z]),
+            Point3D::new([r, r, z]),
+            Point3D::new([-r, r, z]),
+        ]);
+        indices.extend([0, 1, 2, 0, 2, 3].iter().map(|&i| base + i));
+    }
+    Mesh::new(positions, vec![], vec![], indices).unwrap()
+}
+
+fn wireframe(mesh: &Mesh, mode: WireframeMode, image: &mut ImageMeta) -> usize {
+    image.draw_wireframe(mesh, &Wireframe::new(&camera(), GREEN, mode));
+    image
+        .buffer
+        .iter()
+        .filter(|p| p.green == 255 && p.red == 0)
+        .count()
+}
+
+fn shaded(mesh: &Mesh) -> ImageMeta {
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    let mut shader = FlatShader {
+        transform: camera().view_projection_matrix(),
+        light_dir: Point3D::new([0.0, 0.0, -1.0]),
+        color: WHITE,
+    };
+    image.draw_mesh(mesh, &mut shader);
+    image
+}
+
+#[test]
+fn all_edges_are_drawn_once() {
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    let drawn = wireframe(&squares(&[(0.5, 0.0)]), WireframeMode::All, &mut image);
+
+    // a 21 pixel outline around the 20 x 20 square, plus its diagonal.
+    assert_eq!(drawn, 4 * 20 + 19);
+}
+
+#[test]
+fn hidden_line_removes_occluded_edges() {
+    // a small square behind a large one.
+    let mesh = squares(&[(0.5, 0.5), (0.25, -0.5)]);
+    let front = squares(&[(0.5, 0.5)]);
+
+    let all = wireframe(
+        &mesh,
+        WireframeMode::All,
+        &mut ImageMeta::new(40, 40).unwrap(),
+    );
+    let visible = wireframe(
+        &front,
+        WireframeMode::All,
+        &mut ImageMeta::new(40, 40).unwrap(),
+    );
+
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    let hidden_line = wireframe(&mesh, WireframeMode::HiddenLine, &mut image);
+
+    assert!(all > visible);
+    assert_eq!(hidden_line, visible);
+    // the image's own depth is left alone.
+    assert!(image.zbuffer.iter().all(|&z| z == f32::MIN));
+}
+
+#[test]
+fn overlay_is_depth_tested_against_the_image() {
+    let front = squares(&[(0.5, 0.5)]);
+    let back = squares(&[(0.25, -0.5)]);
+
+    // the square's own edges survive the depth test thanks to the offset.
+    let mut image = shaded(&front);
+    let overlay = wireframe(&front, WireframeMode::Overlay, &mut image);
+    let all = wireframe(&front, WireframeMode::All, &mut shaded(&front));
+    assert_eq!(overlay, all);
+
+    // edges behind the square are hidden.
+    let mut image = shaded(&front);
+    assert_eq!(wireframe(&back, WireframeMode::Overlay, &mut image), 0);
+
+    // and edges in front of it are not.
+    let mut image = shaded(&back);
+    assert!(wireframe(&front, WireframeMode::Overlay, &mut image) > 0);
+}
+
+#[test]
+fn edges_are_clipped_to_the_view() {
+    let mut image = ImageMeta::new(40, 40).unwrap();
+    let drawn = wireframe(&squares(&[(3.0, 0.0)]), WireframeMode::All, &mut image);
+
+    // only the diagonal crosses the image.
+    assert_eq!(drawn, 40);
+}
+
+#[test]
+fn offset_follows_depth_at_mid_distance() {
+    let camera = Camera::look_at(
+        Point3D::new([0.0, 0.0, 3.0]),
+        Point3D::zero(),
+        Point3D::new([0.0, 1.0, 0.0]),
+        Projection::Perspective {
+            fovy: std::f32::consts::FRAC_PI_3,
+            aspect: 1.0,
+            near: 0.1,
+            far: 100.0,
+        },
+    );
+    let empty = || Mesh::new(vec![], vec![], vec![], vec![]).unwrap();
+    let green = |mesh: &Mesh, occluder: &Mesh| {
+        let mut image = ImageMeta::new(40, 40).unwrap();
+        let mut shader = FlatShader {
+            transform: camera.view_projection_matrix(),
+            light_dir: Point3D::new([0.0, 0.0, -1.0]),
+            color: WHITE,
+        };
+        image.draw_mesh(occluder, &mut shader);
+        image.draw_wireframe(
+            mesh,
+            &Wireframe::new(&camera, GREEN, WireframeMode::Overlay),
+        );
+        image.buffer.iter().filter(|p| *p == &GREEN).count()
+    };
+
+    // ten units from the camera, 0.3 units of distance are less than 1e-3
+    // of z-buffer depth.
+    let back = squares(&[(2.0, -7.0)]);
+    let occluder = squares(&[(3.0, -6.7)]);
+    assert_eq!(green(&back, &occluder), 0);
+    assert!(green(&back, &back) > 0);
+    assert_eq!(green(&back, &back), green(&back, &empty()));
+
+    // a steeply tilted square keeps its own edges.
+    let positions = vec![
+        Point3D::new([-2.0, -2.0, -9.0]),
+        Point3D::new([2.0, -2.0, -5.0]),
+        Point3D::new([2.0, 2.0, -5.0]),
+        Point3D::new([-2.0, 2.0, -9.0]),
+    ];
+    let tilted = Mesh::new(positions, vec![], vec![], vec![0, 1, 2, 0, 2, 3]).unwrap();
+    assert_eq!(green(&tilted, &tilted), green(&tilted, &empty()));
+}